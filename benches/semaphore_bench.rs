@@ -11,7 +11,7 @@ fn bench_uncontended_try_acquire(c: &mut Criterion) {
         let sem = Semaphore::new(100);
         b.iter(|| {
             let p = sem.try_acquire();
-            black_box(p);
+            let _ = black_box(p);
         });
     });
 }
@@ -22,7 +22,7 @@ fn bench_uncontended_acquire(c: &mut Criterion) {
         b.iter(|| {
             rt.block_on(async {
                 let sem = Semaphore::new(100);
-                let p = sem.acquire().await;
+                let p = sem.acquire().await.unwrap();
                 black_box(p);
             });
         });
@@ -46,7 +46,7 @@ fn bench_contended_varying_concurrency(c: &mut Criterion) {
                         for _ in 0..concurrency {
                             let sem = sem.clone();
                             handles.push(compio::runtime::spawn(async move {
-                                let _p = sem.acquire().await;
+                                let _p = sem.acquire().await.unwrap();
                                 black_box(42);
                             }));
                         }
@@ -70,7 +70,7 @@ fn bench_acquire_release_cycles(c: &mut Criterion) {
             rt.block_on(async {
                 let sem = Semaphore::new(1);
                 for _ in 0..1000 {
-                    let p = sem.acquire().await;
+                    let p = sem.acquire().await.unwrap();
                     drop(p);
                 }
             });
@@ -90,7 +90,7 @@ fn bench_high_permits_low_contention(c: &mut Criterion) {
                 for _ in 0..100 {
                     let sem = sem.clone();
                     handles.push(compio::runtime::spawn(async move {
-                        let _p = sem.acquire().await;
+                        let _p = sem.acquire().await.unwrap();
                         black_box(42);
                     }));
                 }
@@ -0,0 +1,460 @@
+//! Adaptive concurrency limiter built on [`Semaphore`](crate::Semaphore)
+//!
+//! Provides `AdaptiveLimiter`, which bounds concurrency like a semaphore but
+//! adjusts its own limit from feedback reported by permit holders. This replaces
+//! hand-written control loops around `reduce_permits()` / `add_permits()` (e.g.
+//! backing off on file descriptor exhaustion and probing upwards again later).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::{AdaptiveLimiter, LimitAlgorithm};
+//!
+//! # async fn example() {
+//! // Start at 64 in-flight operations, never below 4 or above 1024
+//! let limiter = AdaptiveLimiter::new(64, 4, 1024, LimitAlgorithm::aimd());
+//!
+//! let permit = limiter.acquire().await.unwrap();
+//! match std::fs::File::open("/etc/hosts") {
+//!     Ok(_) => permit.success(),
+//!     Err(e) if e.raw_os_error() == Some(24) => permit.overload(), // EMFILE
+//!     Err(_) => permit.ignore(),
+//! }
+//! # }
+//! ```
+
+use crate::semaphore::{AcquireError, SemaphoreGeneric, SemaphorePermit, TryAcquireError};
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+/// Multiplicative backoff applied by the gradient algorithm on overload
+const GRADIENT_OVERLOAD_BACKOFF: f64 = 0.9;
+
+/// Algorithm used by [`AdaptiveLimiter`] to adjust its limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitAlgorithm {
+    /// Additive increase / multiplicative decrease
+    ///
+    /// Each success while the limiter is at least half utilised raises the limit
+    /// by `increase_by`; each overload multiplies it by `backoff_ratio`.
+    Aimd {
+        /// Amount added to the limit on success
+        increase_by: usize,
+        /// Factor (in `(0, 1)`) the limit is multiplied by on overload
+        backoff_ratio: f64,
+    },
+    /// Vegas-style latency gradient
+    ///
+    /// Compares each sample's latency with the lowest latency seen so far (the
+    /// no-load latency). While latency stays within `tolerance` times the
+    /// minimum the limit grows by about `sqrt(limit)`; as queueing inflates
+    /// latency the limit is pulled down proportionally. `smoothing` (in
+    /// `(0, 1]`) controls how quickly the limit moves towards each new estimate.
+    Gradient {
+        /// Allowed latency inflation over the minimum before backing off (>= 1.0)
+        tolerance: f64,
+        /// Weight given to each new estimate
+        smoothing: f64,
+    },
+}
+
+impl LimitAlgorithm {
+    /// AIMD with defaults: `+1` per success, `x0.9` per overload
+    #[must_use]
+    pub fn aimd() -> Self {
+        LimitAlgorithm::Aimd {
+            increase_by: 1,
+            backoff_ratio: 0.9,
+        }
+    }
+
+    /// Gradient with defaults: tolerance `2.0`, smoothing `0.2`
+    #[must_use]
+    pub fn gradient() -> Self {
+        LimitAlgorithm::Gradient {
+            tolerance: 2.0,
+            smoothing: 0.2,
+        }
+    }
+
+    /// Panic if a parameter is outside its documented range
+    ///
+    /// Out-of-range values (or NaN) would drive the limit to NaN or zero.
+    fn validate(&self) {
+        match *self {
+            LimitAlgorithm::Aimd { backoff_ratio, .. } => assert!(
+                backoff_ratio > 0.0 && backoff_ratio < 1.0,
+                "LimitAlgorithm::Aimd requires 0 < backoff_ratio < 1"
+            ),
+            LimitAlgorithm::Gradient {
+                tolerance,
+                smoothing,
+            } => {
+                assert!(
+                    tolerance.is_finite() && tolerance >= 1.0,
+                    "LimitAlgorithm::Gradient requires a finite tolerance >= 1"
+                );
+                assert!(
+                    smoothing > 0.0 && smoothing <= 1.0,
+                    "LimitAlgorithm::Gradient requires 0 < smoothing <= 1"
+                );
+            }
+        }
+    }
+}
+
+/// Outcome of an operation, reported to the limiter by permit holders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The operation completed normally
+    Success,
+    /// The operation failed because the system is overloaded (e.g. `EMFILE`,
+    /// `EAGAIN`, a timeout); the limiter backs off
+    Overload,
+}
+
+/// An async concurrency limiter that adapts its limit from feedback
+///
+/// Wraps a [`Semaphore`](crate::Semaphore) whose capacity is moved with
+/// `set_max_permits()` between a configurable floor and ceiling. Holders of a
+/// [`LimiterPermit`] report how their operation went; the configured
+/// [`LimitAlgorithm`] turns that feedback into a new limit.
+///
+/// Shrinking the limit never revokes permits that are already held: the
+/// semaphore absorbs the excess as they are released.
+pub struct AdaptiveLimiterGeneric<W: WaiterQueueTrait> {
+    /// Semaphore enforcing the current limit
+    semaphore: SemaphoreGeneric<W>,
+    /// Lowest limit the algorithm may choose
+    min_limit: usize,
+    /// Highest limit the algorithm may choose
+    max_limit: usize,
+    /// Algorithm used to adjust the limit
+    algorithm: LimitAlgorithm,
+    /// Control loop state (only touched when feedback is recorded)
+    state: Mutex<LimiterState>,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type AdaptiveLimiter = AdaptiveLimiterGeneric<WaiterQueue>;
+
+/// Mutable state of the control loop
+struct LimiterState {
+    /// Current limit, kept fractional so small adjustments accumulate
+    limit: f64,
+    /// Lowest latency observed (no-load estimate for the gradient algorithm)
+    min_latency: Option<Duration>,
+}
+
+impl<W: WaiterQueueTrait> AdaptiveLimiterGeneric<W> {
+    /// Create a limiter starting at `initial`, bounded by `min_limit..=max_limit`
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `1 <= min_limit <= initial <= max_limit`, or if a parameter
+    /// of `algorithm` is outside its documented range (NaN included).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::{AdaptiveLimiter, LimitAlgorithm};
+    ///
+    /// let limiter = AdaptiveLimiter::new(16, 1, 256, LimitAlgorithm::gradient());
+    /// assert_eq!(limiter.limit(), 16);
+    /// ```
+    #[must_use]
+    pub fn new(
+        initial: usize,
+        min_limit: usize,
+        max_limit: usize,
+        algorithm: LimitAlgorithm,
+    ) -> Self {
+        assert!(
+            min_limit > 0,
+            "AdaptiveLimiter must allow at least one permit"
+        );
+        assert!(
+            min_limit <= initial && initial <= max_limit,
+            "AdaptiveLimiter requires min_limit <= initial <= max_limit"
+        );
+        algorithm.validate();
+        Self {
+            semaphore: SemaphoreGeneric::new(initial),
+            min_limit,
+            max_limit,
+            algorithm,
+            state: Mutex::new(LimiterState {
+                limit: initial as f64,
+                min_latency: None,
+            }),
+        }
+    }
+
+    /// Acquire a permit, waiting asynchronously if the limit is reached
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcquireError`] if the limiter has been closed.
+    pub async fn acquire(&self) -> Result<LimiterPermit<'_, W>, AcquireError> {
+        let permit = self.semaphore.acquire().await?;
+        Ok(LimiterPermit::new(self, permit))
+    }
+
+    /// Try to acquire a permit without waiting
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Semaphore::try_acquire`](SemaphoreGeneric::try_acquire).
+    pub fn try_acquire(&self) -> Result<LimiterPermit<'_, W>, TryAcquireError> {
+        let permit = self.semaphore.try_acquire()?;
+        Ok(LimiterPermit::new(self, permit))
+    }
+
+    /// Record the outcome of an operation and adjust the limit
+    ///
+    /// Permit holders normally report through [`LimiterPermit::success`] /
+    /// [`LimiterPermit::overload`]; this is for callers that measure operations
+    /// themselves. `in_flight` is the number of operations running when this one
+    /// completed (including itself).
+    pub fn record(&self, outcome: Outcome, latency: Duration, in_flight: usize) {
+        let mut state = self.state.lock();
+        let limit = match (self.algorithm, outcome) {
+            (
+                LimitAlgorithm::Aimd {
+                    increase_by,
+                    backoff_ratio,
+                },
+                outcome,
+            ) => match outcome {
+                // Only probe upwards while the current limit is actually used
+                Outcome::Success if in_flight * 2 >= state.limit as usize => {
+                    state.limit + increase_by as f64
+                }
+                Outcome::Success => state.limit,
+                Outcome::Overload => state.limit * backoff_ratio,
+            },
+            (
+                LimitAlgorithm::Gradient {
+                    tolerance,
+                    smoothing,
+                },
+                Outcome::Success,
+            ) => {
+                let min_latency = state.min_latency.map_or(latency, |min| min.min(latency));
+                state.min_latency = Some(min_latency);
+
+                // Ratio of no-load latency to observed latency, clamped so a
+                // single slow sample cannot more than halve the limit
+                let gradient = if latency.is_zero() {
+                    1.0
+                } else {
+                    (tolerance * min_latency.as_secs_f64() / latency.as_secs_f64()).clamp(0.5, 1.0)
+                };
+                let estimate = state.limit * gradient + state.limit.sqrt();
+                state.limit * (1.0 - smoothing) + estimate * smoothing
+            }
+            (LimitAlgorithm::Gradient { .. }, Outcome::Overload) => {
+                state.limit * GRADIENT_OVERLOAD_BACKOFF
+            }
+        };
+        // A non-finite result (e.g. from an extreme latency) keeps the old limit
+        if limit.is_finite() {
+            state.limit = limit.clamp(self.min_limit as f64, self.max_limit as f64);
+        }
+
+        // Resize while holding the state lock so concurrent feedback is applied in order.
+        // The semaphore needs at least one permit.
+        let new_limit = (state.limit as usize).max(1);
+        if new_limit != self.semaphore.max_permits() {
+            self.semaphore.set_max_permits(new_limit);
+        }
+    }
+
+    /// Get the current limit
+    #[must_use]
+    pub fn limit(&self) -> usize {
+        self.semaphore.max_permits()
+    }
+
+    /// Get the number of permits currently held
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.semaphore.in_use()
+    }
+
+    /// Get the floor and ceiling the limit is kept within
+    #[must_use]
+    pub fn bounds(&self) -> (usize, usize) {
+        (self.min_limit, self.max_limit)
+    }
+
+    /// Close the limiter, failing current and future acquires
+    ///
+    /// See [`Semaphore::close`](SemaphoreGeneric::close).
+    pub fn close(&self) {
+        self.semaphore.close();
+    }
+}
+
+/// RAII guard for an operation admitted by an [`AdaptiveLimiter`]
+///
+/// Report how the operation went with [`success`](Self::success) or
+/// [`overload`](Self::overload). Dropping the permit without reporting (or
+/// calling [`ignore`](Self::ignore)) releases it without adjusting the limit,
+/// which is appropriate for failures unrelated to load.
+pub struct LimiterPermit<'a, W: WaiterQueueTrait> {
+    /// Limiter that issued this permit
+    limiter: &'a AdaptiveLimiterGeneric<W>,
+    /// Underlying semaphore permit (released on drop)
+    _permit: SemaphorePermit<'a, W>,
+    /// When the permit was acquired, for latency measurement
+    start: Instant,
+}
+
+impl<'a, W: WaiterQueueTrait> LimiterPermit<'a, W> {
+    fn new(limiter: &'a AdaptiveLimiterGeneric<W>, permit: SemaphorePermit<'a, W>) -> Self {
+        Self {
+            limiter,
+            _permit: permit,
+            start: Instant::now(),
+        }
+    }
+
+    /// Report that the operation succeeded (latency measured since acquire)
+    pub fn success(self) {
+        self.finish(Outcome::Success);
+    }
+
+    /// Report that the operation failed due to overload, backing off the limit
+    pub fn overload(self) {
+        self.finish(Outcome::Overload);
+    }
+
+    /// Release the permit without adjusting the limit
+    pub fn ignore(self) {
+        drop(self);
+    }
+
+    /// Time elapsed since this permit was acquired
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn finish(self, outcome: Outcome) {
+        // Record while still holding the permit so in_flight counts this operation
+        let in_flight = self.limiter.in_flight();
+        self.limiter.record(outcome, self.elapsed(), in_flight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aimd_increase_and_backoff() {
+        let limiter = AdaptiveLimiter::new(10, 2, 12, LimitAlgorithm::aimd());
+
+        // Under-utilised: no probing upwards
+        limiter.record(Outcome::Success, Duration::from_millis(1), 1);
+        assert_eq!(limiter.limit(), 10);
+
+        limiter.record(Outcome::Success, Duration::from_millis(1), 5);
+        assert_eq!(limiter.limit(), 11);
+
+        limiter.record(Outcome::Overload, Duration::from_millis(1), 5);
+        assert_eq!(limiter.limit(), 9); // 11 * 0.9 = 9.9
+    }
+
+    #[test]
+    fn test_limit_stays_within_bounds() {
+        let limiter = AdaptiveLimiter::new(3, 2, 4, LimitAlgorithm::aimd());
+
+        for _ in 0..10 {
+            limiter.record(Outcome::Success, Duration::from_millis(1), 4);
+        }
+        assert_eq!(limiter.limit(), 4);
+
+        for _ in 0..10 {
+            limiter.record(Outcome::Overload, Duration::from_millis(1), 4);
+        }
+        assert_eq!(limiter.limit(), 2);
+        assert_eq!(limiter.bounds(), (2, 4));
+    }
+
+    #[test]
+    fn test_gradient_follows_latency() {
+        let limiter = AdaptiveLimiter::new(20, 1, 100, LimitAlgorithm::gradient());
+
+        // Latency at the no-load level: limit grows
+        for _ in 0..5 {
+            limiter.record(Outcome::Success, Duration::from_millis(10), 20);
+        }
+        let grown = limiter.limit();
+        assert!(grown > 20, "limit should grow, got {}", grown);
+
+        // Latency far above the minimum: limit shrinks
+        for _ in 0..20 {
+            limiter.record(Outcome::Success, Duration::from_millis(100), 20);
+        }
+        assert!(
+            limiter.limit() < grown,
+            "limit should shrink, got {}",
+            limiter.limit()
+        );
+    }
+
+    #[test]
+    fn test_permit_feedback_uses_in_flight() {
+        let limiter = AdaptiveLimiter::new(2, 1, 10, LimitAlgorithm::aimd());
+
+        let first = limiter.try_acquire().unwrap();
+        let second = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_err());
+
+        first.success();
+        assert_eq!(limiter.limit(), 3);
+
+        second.ignore();
+        assert_eq!(limiter.limit(), 3);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "AdaptiveLimiter requires min_limit <= initial <= max_limit")]
+    fn test_invalid_bounds_panics() {
+        let _limiter = AdaptiveLimiter::new(10, 1, 5, LimitAlgorithm::aimd());
+    }
+
+    #[test]
+    #[should_panic(expected = "LimitAlgorithm::Aimd requires 0 < backoff_ratio < 1")]
+    fn test_nan_backoff_ratio_panics() {
+        let algorithm = LimitAlgorithm::Aimd {
+            increase_by: 1,
+            backoff_ratio: f64::NAN,
+        };
+        let _limiter = AdaptiveLimiter::new(10, 1, 20, algorithm);
+    }
+
+    #[test]
+    #[should_panic(expected = "LimitAlgorithm::Gradient requires 0 < smoothing <= 1")]
+    fn test_out_of_range_smoothing_panics() {
+        let algorithm = LimitAlgorithm::Gradient {
+            tolerance: 2.0,
+            smoothing: 1.5,
+        };
+        let _limiter = AdaptiveLimiter::new(10, 1, 20, algorithm);
+    }
+
+    #[test]
+    #[should_panic(expected = "LimitAlgorithm::Gradient requires a finite tolerance >= 1")]
+    fn test_nan_tolerance_panics() {
+        let algorithm = LimitAlgorithm::Gradient {
+            tolerance: f64::NAN,
+            smoothing: 0.2,
+        };
+        let _limiter = AdaptiveLimiter::new(10, 1, 20, algorithm);
+    }
+}
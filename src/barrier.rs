@@ -0,0 +1,179 @@
+//! Reusable async barrier
+//!
+//! This module provides `Barrier`, which lets a fixed number of tasks wait for
+//! each other before continuing. The barrier resets once every task has
+//! arrived, so the same barrier can separate successive phases of work. One
+//! task per generation is told it is the *leader*, e.g. to run a step that
+//! must happen once between phases.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::Barrier;
+//! use std::sync::Arc;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let barrier = Arc::new(Barrier::new(4));
+//!
+//!     let mut handles = Vec::new();
+//!     for i in 0..4 {
+//!         let barrier = barrier.clone();
+//!         handles.push(compio::runtime::spawn(async move {
+//!             println!("task {} scanning", i);
+//!             if barrier.wait().await.is_leader() {
+//!                 println!("all scans done, planning");
+//!             }
+//!             barrier.wait().await;
+//!             println!("task {} copying", i);
+//!         }));
+//!     }
+//!     for handle in handles {
+//!         handle.await.unwrap();
+//!     }
+//! }
+//! ```
+
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use parking_lot::Mutex;
+use std::fmt;
+
+/// A barrier that releases tasks once `n` of them are waiting
+///
+/// Generic over the waiter queue implementation; use the [`Barrier`] alias.
+///
+/// # Cancellation
+///
+/// A task counts as arrived once its `wait()` future is first polled. Dropping
+/// the future afterwards does not withdraw it, so the generation still
+/// completes when the remaining tasks arrive.
+pub struct BarrierGeneric<W: WaiterQueueTrait> {
+    state: Mutex<BarrierState>,
+    /// Number of tasks per generation
+    n: usize,
+    waiters: W,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type Barrier = BarrierGeneric<WaiterQueue>;
+
+struct BarrierState {
+    /// Tasks that have arrived in the current generation
+    arrived: usize,
+    /// Bumped each time the barrier releases its waiters
+    generation: u64,
+}
+
+impl<W: WaiterQueueTrait> BarrierGeneric<W> {
+    /// Create a barrier releasing tasks in groups of `n`
+    ///
+    /// A barrier of 0 behaves like a barrier of 1: every `wait()` returns
+    /// immediately as leader.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::Barrier;
+    ///
+    /// let barrier = Barrier::new(3);
+    /// ```
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            state: Mutex::new(BarrierState {
+                arrived: 0,
+                generation: 0,
+            }),
+            n: n.max(1),
+            waiters: W::new(),
+        }
+    }
+
+    /// Wait until `n` tasks have called `wait()` in this generation
+    ///
+    /// Exactly one task per generation, the last to arrive, gets a result whose
+    /// [`is_leader`](BarrierWaitResult::is_leader) is `true`. The barrier then
+    /// resets for the next generation.
+    pub async fn wait(&self) -> BarrierWaitResult {
+        let generation = {
+            let mut state = self.state.lock();
+            state.arrived += 1;
+            if state.arrived == self.n {
+                state.arrived = 0;
+                state.generation = state.generation.wrapping_add(1);
+                drop(state);
+                self.waiters.wake_all();
+                return BarrierWaitResult(true);
+            }
+            state.generation
+        };
+
+        let state = &self.state;
+        while state.lock().generation == generation {
+            // CRITICAL: re-check during registration so a release between the
+            // check above and registering is not missed
+            self.waiters
+                .add_waiter_if(|| state.lock().generation != generation)
+                .await;
+        }
+        BarrierWaitResult(false)
+    }
+}
+
+impl<W: WaiterQueueTrait> fmt::Debug for BarrierGeneric<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("Barrier")
+            .field("n", &self.n)
+            .field("arrived", &state.arrived)
+            .field("generation", &state.generation)
+            .finish()
+    }
+}
+
+/// Result of `Barrier::wait()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    /// Whether this task completed the generation
+    ///
+    /// Exactly one task per generation is the leader.
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[compio::test]
+    async fn test_single_task_is_always_leader() {
+        let barrier = Barrier::new(1);
+        assert!(barrier.wait().await.is_leader());
+        assert!(barrier.wait().await.is_leader());
+
+        // Zero is treated as one
+        assert!(Barrier::new(0).wait().await.is_leader());
+    }
+
+    #[compio::test]
+    async fn test_generation_resets() {
+        let barrier = std::sync::Arc::new(Barrier::new(2));
+        for round in 1..=3 {
+            let follower = {
+                let barrier = barrier.clone();
+                compio::runtime::spawn(async move { barrier.wait().await })
+            };
+            compio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+            assert!(barrier.wait().await.is_leader());
+            assert!(!follower.await.unwrap().is_leader());
+
+            let state = barrier.state.lock();
+            assert_eq!(state.arrived, 0);
+            assert_eq!(state.generation, round);
+        }
+    }
+}
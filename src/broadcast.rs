@@ -0,0 +1,411 @@
+//! Multi-producer, multi-consumer broadcast channel
+//!
+//! [`channel(cap)`](channel) creates a channel where every [`Receiver`] sees
+//! every message sent after it subscribed. The last `cap` messages are kept
+//! in a ring buffer. Senders never wait: a receiver that falls more than `cap`
+//! messages behind loses the oldest ones and is told how many through
+//! [`RecvError::Lagged`], then continues from the oldest message still kept.
+//!
+//! Waiting receivers are parked on the crate's [`WaiterQueue`] and released
+//! with `wake_all()` on each send, since every one of them has a new message
+//! to read.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::broadcast;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let (tx, mut ui) = broadcast::channel(64);
+//!     let mut log = tx.subscribe();
+//!
+//!     compio::runtime::spawn(async move {
+//!         while let Ok(done) = log.recv().await {
+//!             println!("log: {} files copied", done);
+//!         }
+//!     })
+//!     .detach();
+//!
+//!     for done in 1..=3 {
+//!         tx.send(done).unwrap();
+//!     }
+//!     drop(tx);
+//!
+//!     loop {
+//!         match ui.recv().await {
+//!             Ok(done) => println!("ui: {} files copied", done),
+//!             Err(broadcast::RecvError::Lagged(n)) => println!("ui: skipped {}", n),
+//!             Err(broadcast::RecvError::Closed) => break,
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::waiter_queue::WaiterQueue;
+use parking_lot::Mutex;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Create a broadcast channel keeping the last `capacity` messages
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+///
+/// # Example
+///
+/// ```rust
+/// use compio_sync::broadcast;
+///
+/// let (tx, mut rx1) = broadcast::channel(4);
+/// let mut rx2 = tx.subscribe();
+/// tx.send("hello").unwrap();
+/// assert_eq!(rx1.try_recv(), Ok("hello"));
+/// assert_eq!(rx2.try_recv(), Ok("hello"));
+/// ```
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast channel requires capacity > 0");
+    let shared = Arc::new(Shared {
+        buffer: Mutex::new((0..capacity).map(|_| None).collect()),
+        tail: AtomicU64::new(0),
+        senders: AtomicUsize::new(1),
+        receivers: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+        waiters: WaiterQueue::new(),
+    });
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, next: 0 },
+    )
+}
+
+/// State shared by all senders and receivers
+struct Shared<T> {
+    /// Message at position `pos` lives in slot `pos % capacity`
+    buffer: Mutex<Box<[Option<T>]>>,
+    /// Position of the next message; only advanced with `buffer` locked
+    tail: AtomicU64,
+    /// Live `Sender` handles
+    senders: AtomicUsize,
+    /// Live `Receiver` handles
+    receivers: AtomicUsize,
+    /// Set when the last sender is dropped
+    closed: AtomicBool,
+    /// Receivers waiting for a message
+    waiters: WaiterQueue,
+}
+
+impl<T> Shared<T> {
+    fn capacity(&self) -> u64 {
+        self.buffer.lock().len() as u64
+    }
+}
+
+/// Sending half of a broadcast channel; clone it to add producers
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send a message to every current receiver; never waits
+    ///
+    /// Returns the number of receivers the message was sent to. If the buffer
+    /// is full, the oldest message is overwritten and receivers that had not
+    /// read it will see [`RecvError::Lagged`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`] with the message if there are no receivers.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let receivers = self.shared.receivers.load(Ordering::SeqCst);
+        if receivers == 0 {
+            return Err(SendError(value));
+        }
+        {
+            let mut buffer = self.shared.buffer.lock();
+            let tail = self.shared.tail.load(Ordering::SeqCst);
+            let slot = (tail % buffer.len() as u64) as usize;
+            buffer[slot] = Some(value);
+            self.shared.tail.store(tail + 1, Ordering::SeqCst);
+        }
+        self.shared.waiters.wake_all();
+        Ok(receivers)
+    }
+
+    /// Create a receiver that sees messages sent after this call
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.shared.receivers.fetch_add(1, Ordering::SeqCst);
+        // Lock so the position cannot move between reading it and counting the
+        // receiver
+        let _buffer = self.shared.buffer.lock();
+        Receiver {
+            shared: self.shared.clone(),
+            next: self.shared.tail.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Get the number of live receivers
+    pub fn receiver_count(&self) -> usize {
+        self.shared.receivers.load(Ordering::SeqCst)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::SeqCst);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shared.closed.store(true, Ordering::SeqCst);
+            self.shared.waiters.wake_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("receivers", &self.receiver_count())
+            .finish()
+    }
+}
+
+/// Receiving half of a broadcast channel
+///
+/// Create more receivers with [`Sender::subscribe`] or
+/// [`Receiver::resubscribe`].
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    /// Position of the next message this receiver reads
+    next: u64,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receive the next message, waiting until one is sent
+    ///
+    /// # Errors
+    ///
+    /// - [`RecvError::Lagged`] if messages were overwritten before this
+    ///   receiver read them; the next call returns the oldest message kept
+    /// - [`RecvError::Closed`] once every sender is dropped and all messages
+    ///   were read
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Lagged(n)) => return Err(RecvError::Lagged(n)),
+                Err(TryRecvError::Closed) => return Err(RecvError::Closed),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            // CRITICAL: check during registration so a send or the last sender
+            // dropping between try_recv() and registering is not missed
+            let (tail, closed, next) = (&self.shared.tail, &self.shared.closed, self.next);
+            self.shared
+                .waiters
+                .add_waiter_if(|| {
+                    tail.load(Ordering::SeqCst) != next || closed.load(Ordering::SeqCst)
+                })
+                .await;
+        }
+    }
+
+    /// Receive the next message if one is available
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if no new message has been sent
+    /// - [`TryRecvError::Lagged`] if messages were overwritten before this
+    ///   receiver read them
+    /// - [`TryRecvError::Closed`] once every sender is dropped and all messages
+    ///   were read
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        // Read `closed` first: a send before the last sender dropped is then
+        // visible in `tail` below
+        let closed = self.shared.closed.load(Ordering::SeqCst);
+        let buffer = self.shared.buffer.lock();
+        let tail = self.shared.tail.load(Ordering::SeqCst);
+        if self.next == tail {
+            return Err(if closed {
+                TryRecvError::Closed
+            } else {
+                TryRecvError::Empty
+            });
+        }
+
+        let capacity = buffer.len() as u64;
+        let oldest = tail.saturating_sub(capacity);
+        if self.next < oldest {
+            let missed = oldest - self.next;
+            self.next = oldest;
+            return Err(TryRecvError::Lagged(missed));
+        }
+
+        let value = buffer[(self.next % capacity) as usize]
+            .clone()
+            .expect("slot within the kept range is filled");
+        self.next += 1;
+        Ok(value)
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Create a receiver that sees messages sent after this call
+    ///
+    /// Unlike cloning, the new receiver does not see messages this one has not
+    /// read yet.
+    pub fn resubscribe(&self) -> Self {
+        self.shared.receivers.fetch_add(1, Ordering::SeqCst);
+        let _buffer = self.shared.buffer.lock();
+        Receiver {
+            shared: self.shared.clone(),
+            next: self.shared.tail.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Get the number of messages this receiver has not read yet, including
+    /// any that were already overwritten
+    pub fn len(&self) -> usize {
+        (self.shared.tail.load(Ordering::SeqCst) - self.next) as usize
+    }
+
+    /// Check whether this receiver has read every message sent so far
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.receivers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("len", &self.len())
+            .field("capacity", &self.shared.capacity())
+            .finish()
+    }
+}
+
+/// Error returned by `Sender::send()` when there are no receivers, holding the
+/// message
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel has no receivers")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+/// Error returned by `Receiver::recv()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// Every sender is dropped and all messages were read
+    Closed,
+    /// This many messages were overwritten before the receiver read them
+    Lagged(u64),
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Closed => f.write_str("channel closed"),
+            RecvError::Lagged(n) => write!(f, "receiver lagged by {} messages", n),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Error returned by `Receiver::try_recv()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No new message has been sent
+    Empty,
+    /// Every sender is dropped and all messages were read
+    Closed,
+    /// This many messages were overwritten before the receiver read them
+    Lagged(u64),
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("channel empty"),
+            TryRecvError::Closed => f.write_str("channel closed"),
+            TryRecvError::Lagged(n) => write!(f, "receiver lagged by {} messages", n),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lagged_receiver_skips_to_oldest() {
+        let (tx, mut rx) = channel(2);
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(rx.len(), 5);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Lagged(3)));
+        assert_eq!(rx.try_recv(), Ok(3));
+        assert_eq!(rx.try_recv(), Ok(4));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_subscribe_starts_at_tail() {
+        let (tx, mut rx) = channel(4);
+        tx.send(1).unwrap();
+        let mut late = tx.subscribe();
+        let mut later = rx.resubscribe();
+        tx.send(2).unwrap();
+
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(late.try_recv(), Ok(2));
+        assert_eq!(later.try_recv(), Ok(2));
+        assert_eq!(tx.receiver_count(), 3);
+    }
+
+    #[test]
+    fn test_send_without_receivers_fails() {
+        let (tx, rx) = channel(1);
+        drop(rx);
+        assert_eq!(tx.send(7), Err(SendError(7)));
+    }
+
+    #[test]
+    fn test_closed_after_draining() {
+        let (tx, mut rx) = channel(2);
+        tx.send(1).unwrap();
+        drop(tx);
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    }
+}
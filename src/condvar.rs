@@ -16,36 +16,46 @@
 //!     let cv_clone = cv.clone();
 //!     
 //!     // Spawn a task that waits for notification
-//!     compio::runtime::spawn(async move {
+//!     let handle = compio::runtime::spawn(async move {
 //!         cv_clone.wait().await;
 //!         println!("Notified!");
 //!     });
 //!     
 //!     // Do some work...
 //!     
-//!     // Notify the waiting task
+//!     // Notify the waiting task (a notification with no waiter is lost)
 //!     cv.notify_one();
+//!     handle.await.unwrap();
 //! }
 //! ```
 
+use crate::mutex::{MutexGeneric, MutexGuard, OwnedMutexGuard};
+use crate::notify::NotifyGeneric;
 use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// A compio-compatible async condition variable for task notification
 ///
 /// `Condvar` allows one or more tasks to wait for a notification from another task.
 /// Unlike `std::sync::Condvar`, this implementation:
 /// - Works with async/await and compio's runtime
-/// - Does not require an external mutex (uses interior mutability)
+/// - Does not require an external mutex (uses interior mutability), but can be
+///   paired with [`Mutex`](crate::Mutex) through [`wait_guard`](Self::wait_guard)
 /// - Users should wrap in `Arc<Condvar>` when sharing between tasks
 ///
-/// # Memory Safety
+/// # Notification Semantics
 ///
-/// This implementation uses CAREFUL memory ordering to prevent lost wakeups:
-/// - The `notified` flag is checked INSIDE the mutex to prevent TOCTOU races
-/// - All notifier operations (set flag + drain) happen atomically under mutex
-/// - Waiter operations (check flag + register) happen atomically under mutex
-/// - All accesses use proper Acquire/Release semantics
+/// Notifications are counted per generation rather than stored in a flag:
+/// - A waiter only completes for notifications issued after it registered
+///   (first poll of `wait()`, or before the guard is released in `wait_guard()`)
+/// - `notify_one()` completes exactly one registered waiter; with no waiters it
+///   has no effect
+/// - `notify_all()` completes every registered waiter; later waiters block
+/// - `wait_until()` / `wait_while()` waiters re-check their predicate on every
+///   notification
 ///
 /// # Usage Pattern
 ///
@@ -93,33 +103,31 @@ pub type Condvar = CondvarGeneric<WaiterQueue>;
 /// Internal state using shared waiter queue abstraction
 ///
 /// CRITICAL RACE PREVENTION:
-/// The `notified` flag MUST be checked while holding the queue's internal mutex
-/// to prevent this race:
+/// A waiter MUST be counted as registered before it can miss a notification:
 ///
-/// WITHOUT atomic check-and-add:
-/// 1. Waiter: check notified → false (no lock)
-/// 2. Notifier: set notified → true
-/// 3. Notifier: drain waiters
-/// 4. Waiter: add to waiters → LOST WAKEUP!
+/// WITHOUT registration before sleeping:
+/// 1. Waiter: check state → not ready (no lock)
+/// 2. Notifier: change state, notify_one() → nobody registered, dropped
+/// 3. Waiter: sleep → LOST WAKEUP!
 ///
-/// WITH atomic check-and-add (WaiterQueue):
-/// 1. Waiter: lock, check notified → false, add to waiters, unlock
-/// 2. Notifier: lock, set notified → true, drain waiters, unlock
+/// WITH generation tracking (`NotifyGeneric` without permits):
+/// 1. Waiter: register in the current generation, then check / release guard
+/// 2. Notifier: notify_one() → ticket for the oldest registered waiter, wake_one()
+/// 3. Waiter: claims the ticket under the WaiterQueue's check-and-add
 ///
-/// The WaiterQueue encapsulates this pattern for reuse across sync primitives.
+/// Predicate waiters get the same guarantee from evaluating the predicate in
+/// the WaiterQueue's check-and-add.
 struct CondvarInner<W: WaiterQueueTrait> {
-    /// Notification flag (true = notified, wake immediately)
-    notified: AtomicBool,
+    /// Generation / ticket bookkeeping for `wait()` and `wait_guard()`
+    notify: NotifyGeneric<W>,
 
-    /// Waiter queue abstraction (handles mutex + check-and-add pattern)
-    waiters: W,
+    /// Tasks in `wait_until()` / `wait_while()`, re-checked on every notify
+    predicate_waiters: W,
 }
 
 impl<W: WaiterQueueTrait + Sync> CondvarGeneric<W> {
     /// Create a new condition variable
     ///
-    /// The condition variable starts in the "not notified" state.
-    ///
     /// # Example
     ///
     /// ```rust,no_run
@@ -133,16 +141,23 @@ impl<W: WaiterQueueTrait + Sync> CondvarGeneric<W> {
     pub fn new() -> Self {
         Self {
             inner: CondvarInner {
-                notified: AtomicBool::new(false),
-                waiters: W::new(),
+                notify: NotifyGeneric::without_permits(),
+                predicate_waiters: W::new(),
             },
         }
     }
 
     /// Wait for notification
     ///
-    /// Suspends the current task until `notify_one()` or `notify_all()` is called.
-    /// If the condition variable is already notified, returns immediately.
+    /// Suspends the current task until a `notify_one()` or `notify_all()` issued
+    /// after this wait first polled. Notifications sent before that are not
+    /// seen, so check shared state in a loop, or use
+    /// [`wait_until`](Self::wait_until) / [`wait_guard`](Self::wait_guard).
+    ///
+    /// # Cancel safety
+    ///
+    /// If the future is dropped after `notify_one()` picked it, the notification
+    /// is passed on to another waiter.
     ///
     /// # Example
     ///
@@ -155,70 +170,151 @@ impl<W: WaiterQueueTrait + Sync> CondvarGeneric<W> {
     /// # }
     /// ```
     pub async fn wait(&self) {
-        loop {
-            // Wait for notification
-            self.inner
-                .waiters
-                .add_waiter_if(|| self.inner.notified.load(Ordering::Acquire))
-                .await;
-
-            // Re-check condition after wake
-            if self.inner.notified.load(Ordering::Acquire) {
-                break;
-            }
-        }
+        self.inner.notify.notified().await;
     }
 
-    /// Notify one waiting task
+    /// Atomically release a mutex guard, wait for a notification, and relock
+    ///
+    /// This is the classic condition-variable wait: the waiter registers before
+    /// the guard is released, so a notifier that takes the lock, changes the
+    /// state and calls `notify_one()` / `notify_all()` cannot be missed.
+    ///
+    /// Accepts a [`MutexGuard`] or an [`OwnedMutexGuard`] and returns the same
+    /// kind of guard. It is the `wait(guard)` of other condition variables; the
+    /// name differs because [`wait`](Self::wait) without a guard already exists
+    /// and Rust has no overloading.
+    ///
+    /// Like any condition variable, check the predicate in a loop: another
+    /// task may change the state between the notification and relocking.
     ///
-    /// Wakes up one task currently waiting on `wait()`. If no tasks are waiting,
-    /// sets a flag so the next call to `wait()` returns immediately.
+    /// # Cancel safety
+    ///
+    /// If the future is dropped while waiting, the lock is not reacquired. A
+    /// notification it had already been picked for is passed on to another
+    /// waiter.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::{Condvar, Mutex};
+    ///
+    /// # async fn example() {
+    /// let queue = Mutex::new(Vec::<u32>::new());
+    /// let cv = Condvar::new();
+    ///
+    /// let mut items = queue.lock().await;
+    /// while items.is_empty() {
+    ///     items = cv.wait_guard(items).await;
+    /// }
+    /// let item = items.pop();
+    /// # }
+    /// ```
+    pub async fn wait_guard<G: CondvarGuard>(&self, guard: G) -> G {
+        // Register while still holding the lock: a notifier must take the lock
+        // to change the shared state, so any notify for that change counts
+        let mut notified = pin!(self.inner.notify.notified());
+        notified.as_mut().enable();
+        let mutex = guard.unlock();
+
+        notified.await;
+        G::relock(mutex).await
+    }
+
+    /// Wait until `predicate` returns `true`
+    ///
+    /// Replaces the check / `wait()` / re-check loop. The predicate is evaluated
+    /// inside the waiter queue's registration check, so a notifier that updates
+    /// the state and then calls `notify_one()` / `notify_all()` cannot slip in
+    /// between the check and going to sleep.
+    ///
+    /// Returns immediately if the predicate already holds. Every notification,
+    /// including `notify_one()`, makes all predicate waiters re-check, so they
+    /// never consume a notification meant for a `wait()` caller.
     ///
     /// # Example
     ///
     /// ```rust,no_run
     /// use compio_sync::Condvar;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
     ///
     /// # async fn example() {
+    /// let done = AtomicUsize::new(0);
     /// let cv = Condvar::new();
-    /// cv.notify_one();
+    ///
+    /// // Elsewhere: done.fetch_add(1, Ordering::SeqCst); cv.notify_all();
+    /// cv.wait_until(|| done.load(Ordering::SeqCst) == 10).await;
     /// # }
     /// ```
-    pub fn notify_one(&self) {
-        // Set notified flag (uses Release ordering for memory synchronization)
-        self.inner.notified.store(true, Ordering::Release);
+    pub async fn wait_until<F>(&self, predicate: F)
+    where
+        F: Fn() -> bool + Send + Sync,
+    {
+        while !predicate() {
+            self.inner.predicate_waiters.add_waiter_if(&predicate).await;
+        }
+    }
 
-        // Wake one waiter (WaiterQueue handles lock-then-wake pattern)
-        self.inner.waiters.wake_one();
+    /// Wait while `condition` returns `true`
+    ///
+    /// The inverse of [`wait_until`](Self::wait_until), mirroring
+    /// `std::sync::Condvar::wait_while`.
+    pub async fn wait_while<F>(&self, condition: F)
+    where
+        F: Fn() -> bool + Send + Sync,
+    {
+        self.wait_until(|| !condition()).await;
     }
 
-    /// Notify all waiting tasks
+    /// Wait until `predicate` returns `true`, giving up after `timeout`
     ///
-    /// Wakes up all tasks currently waiting on `wait()`. Also sets a flag so that
-    /// future calls to `wait()` return immediately without blocking.
+    /// Returns whether the predicate was satisfied. It is evaluated once more
+    /// when the timeout expires, so a state change that raced with the timer is
+    /// still reported as `true`.
     ///
     /// # Example
     ///
     /// ```rust,no_run
     /// use compio_sync::Condvar;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::time::Duration;
     ///
     /// # async fn example() {
+    /// let ready = AtomicBool::new(false);
     /// let cv = Condvar::new();
-    /// cv.notify_all();
+    ///
+    /// if !cv
+    ///     .wait_until_timeout(|| ready.load(Ordering::Acquire), Duration::from_secs(1))
+    ///     .await
+    /// {
+    ///     eprintln!("gave up waiting");
+    /// }
     /// # }
     /// ```
-    pub fn notify_all(&self) {
-        // Set notified flag (uses Release ordering for memory synchronization)
-        self.inner.notified.store(true, Ordering::Release);
+    pub async fn wait_until_timeout<F>(&self, predicate: F, timeout: Duration) -> bool
+    where
+        F: Fn() -> bool + Send + Sync,
+    {
+        compio::time::timeout(timeout, self.wait_until(&predicate))
+            .await
+            .is_ok()
+            || predicate()
+    }
 
-        // Wake all waiters (WaiterQueue handles lock-then-wake pattern)
-        self.inner.waiters.wake_all();
+    /// Wait while `condition` returns `true`, giving up after `timeout`
+    ///
+    /// Returns whether the wait ended because `condition` became `false`.
+    pub async fn wait_while_timeout<F>(&self, condition: F, timeout: Duration) -> bool
+    where
+        F: Fn() -> bool + Send + Sync,
+    {
+        self.wait_until_timeout(|| !condition(), timeout).await
     }
 
-    /// Clear the notification flag
+    /// Notify one waiting task
     ///
-    /// Resets the condition variable to the "not notified" state.
-    /// Future calls to `wait()` will block until `notify_one()` or `notify_all()` is called.
+    /// Completes exactly one task currently waiting in `wait()` or
+    /// `wait_guard()`, and makes predicate waiters re-check. If no task is
+    /// waiting, the notification is lost.
     ///
     /// # Example
     ///
@@ -228,14 +324,31 @@ impl<W: WaiterQueueTrait + Sync> CondvarGeneric<W> {
     /// # async fn example() {
     /// let cv = Condvar::new();
     /// cv.notify_one();
-    /// cv.clear();  // Reset notification
-    /// cv.wait().await;  // Will block again
     /// # }
     /// ```
-    pub fn clear(&self) {
-        // Use Release ordering to ensure threads don't observe stale 'true' after clear
-        // Pairs with Acquire loads in wait()
-        self.inner.notified.store(false, Ordering::Release);
+    pub fn notify_one(&self) {
+        self.inner.notify.notify_one();
+        self.inner.predicate_waiters.wake_all();
+    }
+
+    /// Notify all waiting tasks
+    ///
+    /// Completes every task currently waiting. Tasks that start waiting
+    /// afterwards block until the next notification.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::Condvar;
+    ///
+    /// # async fn example() {
+    /// let cv = Condvar::new();
+    /// cv.notify_all();
+    /// # }
+    /// ```
+    pub fn notify_all(&self) {
+        self.inner.notify.notify_waiters();
+        self.inner.predicate_waiters.wake_all();
     }
 
     /// Get the number of tasks waiting on this condvar
@@ -244,7 +357,7 @@ impl<W: WaiterQueueTrait + Sync> CondvarGeneric<W> {
     /// **Note**: The count may be stale by the time you read it due to concurrent notify operations.
     #[must_use]
     pub fn waiter_count(&self) -> usize {
-        self.inner.waiters.waiter_count()
+        self.inner.notify.waiters.waiter_count() + self.inner.predicate_waiters.waiter_count()
     }
 }
 
@@ -254,10 +367,59 @@ impl<W: WaiterQueueTrait + Sync> Default for CondvarGeneric<W> {
     }
 }
 
+/// A mutex guard that `Condvar::wait_guard()` can release and reacquire
+///
+/// Implemented for [`MutexGuard`] and [`OwnedMutexGuard`]; sealed.
+pub trait CondvarGuard: sealed::Sealed + Sized {
+    /// Handle used to lock the mutex again
+    #[doc(hidden)]
+    type Mutex;
+
+    /// Release the lock, keeping a handle to the mutex
+    #[doc(hidden)]
+    fn unlock(self) -> Self::Mutex;
+
+    /// Lock the mutex again
+    #[doc(hidden)]
+    fn relock(mutex: Self::Mutex) -> impl Future<Output = Self>;
+}
+
+impl<'a, T, M: WaiterQueueTrait> CondvarGuard for MutexGuard<'a, T, M> {
+    type Mutex = &'a MutexGeneric<T, M>;
+
+    fn unlock(self) -> Self::Mutex {
+        self.mutex
+    }
+
+    fn relock(mutex: Self::Mutex) -> impl Future<Output = Self> {
+        mutex.lock()
+    }
+}
+
+impl<T, M: WaiterQueueTrait> CondvarGuard for OwnedMutexGuard<T, M> {
+    type Mutex = Arc<MutexGeneric<T, M>>;
+
+    fn unlock(self) -> Self::Mutex {
+        OwnedMutexGuard::mutex(&self).clone()
+    }
+
+    fn relock(mutex: Self::Mutex) -> impl Future<Output = Self> {
+        mutex.lock_owned()
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<T, M: crate::waiter_queue::WaiterQueueTrait> Sealed for crate::mutex::MutexGuard<'_, T, M> {}
+    impl<T, M: crate::waiter_queue::WaiterQueueTrait> Sealed for crate::mutex::OwnedMutexGuard<T, M> {}
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::waiter_queue::{WaiterQueue as PlatformWaiterQueue, WaiterQueueTrait};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     use std::sync::Arc;
     use std::sync::Mutex;
 
@@ -313,60 +475,84 @@ mod tests {
     }
 
     #[compio::test]
-    async fn test_condvar_already_notified() {
+    async fn test_condvar_notify_without_waiters_is_lost() {
         let cv = Condvar::new();
         cv.notify_one();
 
-        // Should return immediately since already notified
-        cv.wait().await;
+        // No sticky flag: the earlier notification is not seen
+        let result = compio::time::timeout(std::time::Duration::from_millis(20), cv.wait()).await;
+        assert!(result.is_err());
     }
 
     #[compio::test]
-    async fn test_condvar_clear() {
-        let cv = Condvar::new();
-        cv.notify_one();
+    async fn test_condvar_notify_all_before_wait_is_lost() {
+        let cv = Arc::new(Condvar::new());
 
-        // Clear and verify it returns immediately (still notified)
-        cv.clear();
+        // Notify all before any waiters
+        cv.notify_all();
 
-        // After clear, wait should block (but we won't test blocking here)
-        // Just verify clear doesn't panic
+        let result = compio::time::timeout(std::time::Duration::from_millis(20), cv.wait()).await;
+        assert!(result.is_err());
     }
 
     #[compio::test]
-    async fn test_condvar_notify_before_wait() {
+    async fn test_condvar_notify_one_completes_exactly_one() {
         let cv = Arc::new(Condvar::new());
+        let woken = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..3 {
+            let cv = cv.clone();
+            let woken = woken.clone();
+            compio::runtime::spawn(async move {
+                cv.wait().await;
+                woken.fetch_add(1, Ordering::SeqCst);
+            })
+            .detach();
+        }
+        compio::time::sleep(std::time::Duration::from_millis(10)).await;
 
-        // Notify before any waiter
         cv.notify_one();
+        compio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert_eq!(woken.load(Ordering::SeqCst), 1);
 
-        // Waiter should return immediately
-        cv.wait().await;
+        cv.notify_all();
+        compio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert_eq!(woken.load(Ordering::SeqCst), 3);
     }
 
     #[compio::test]
-    async fn test_condvar_notify_all() {
-        let cv = Arc::new(Condvar::new());
+    async fn test_condvar_late_waiter_cannot_take_notification() {
+        use std::future::Future;
+        use std::task::{Context, Waker};
 
-        // Notify all before any waiters
-        cv.notify_all();
+        let cv = Condvar::new();
+        let mut cx = Context::from_waker(Waker::noop());
 
-        // Multiple waiters should all return immediately
-        cv.wait().await;
-        cv.wait().await;
-        cv.wait().await;
+        let mut first = Box::pin(cv.wait());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+
+        cv.notify_one();
+
+        // Started waiting after notify_one(): the notification is not for it
+        let mut second = Box::pin(cv.wait());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert!(first.as_mut().poll(&mut cx).is_ready());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
     }
 
     #[test]
     fn test_condvar_creation() {
         let cv = Condvar::new();
-        assert!(!cv.inner.notified.load(Ordering::Relaxed));
+        #[cfg(not(target_os = "linux"))]
+        assert_eq!(cv.waiter_count(), 0);
+        let _ = cv;
     }
 
     /// Test that notify_one() during registration works correctly
     ///
-    /// Condvar uses proper condition (|| notified.load()), so notification
-    /// during registration should be caught by the re-check.
+    /// The waiter is counted before it registers on the queue, so a
+    /// notification during registration is handed to it as a ticket and caught
+    /// by the re-check.
     #[compio::test]
     async fn test_mock_notify_during_registration() {
         compio::time::timeout(std::time::Duration::from_secs(2), async {
@@ -374,7 +560,7 @@ mod tests {
 
             // Set up mock to notify during registration
             let cv_clone = cv.clone();
-            cv.inner.waiters.set_on_add_waiter(move || {
+            cv.inner.notify.waiters.set_on_add_waiter(move || {
                 // Notify during registration (race window)
                 cv_clone.notify_one();
             });
@@ -396,7 +582,7 @@ mod tests {
 
             // Set up mock to notify_all during registration
             let cv_clone = cv.clone();
-            cv.inner.waiters.set_on_add_waiter(move || {
+            cv.inner.notify.waiters.set_on_add_waiter(move || {
                 cv_clone.notify_all();
             });
 
@@ -409,49 +595,171 @@ mod tests {
         .expect("Test timed out");
     }
 
-    /// Test that clear() during registration doesn't cause issues
+    /// A notification issued before the waiter registered must not complete it,
+    /// even if it lands right before the queue registration
     #[compio::test]
-    async fn test_mock_clear_during_registration() {
+    async fn test_mock_notify_before_registration_not_seen() {
         compio::time::timeout(std::time::Duration::from_secs(2), async {
             let cv = Arc::new(CondvarGeneric::<MockWaiterQueue>::new());
 
-            // Pre-notify so condition would be true
+            // Notify with nobody registered
             cv.notify_one();
+            cv.notify_all();
 
-            // Set up mock to clear during registration
-            let cv_clone = cv.clone();
-            cv.inner.waiters.set_on_add_waiter(move || {
-                // Clear notification during registration
-                cv_clone.clear();
-            });
-
-            // Should timeout (notification was cleared before re-check)
             let result =
                 compio::time::timeout(std::time::Duration::from_millis(200), cv.wait()).await;
 
-            assert!(result.is_err(), "Should timeout - notification was cleared");
+            assert!(
+                result.is_err(),
+                "Should timeout - notifications predate wait"
+            );
         })
         .await
         .expect("Test timed out");
     }
 
+    /// A notify issued while the waiter is registering must not be lost, and
+    /// the guard must be released before registration
+    #[compio::test]
+    async fn test_mock_wait_guard_notify_during_registration() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let cv = Arc::new(CondvarGeneric::<MockWaiterQueue>::new());
+            let mutex = Arc::new(crate::Mutex::new(false));
+
+            let (cv_clone, mutex_clone) = (cv.clone(), mutex.clone());
+            cv.inner.notify.waiters.set_on_add_waiter(move || {
+                // The lock has been released by now
+                *mutex_clone.try_lock().expect("guard should be released") = true;
+                cv_clone.notify_one();
+            });
+
+            let guard = mutex.lock().await;
+            let guard =
+                compio::time::timeout(std::time::Duration::from_millis(500), cv.wait_guard(guard))
+                    .await
+                    .expect("notification during registration was lost");
+            assert!(*guard);
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    /// An earlier notification must not complete `wait_guard()`
+    #[compio::test]
+    async fn test_wait_guard_ignores_earlier_notify() {
+        let cv = Condvar::new();
+        let mutex = crate::Mutex::new(());
+        cv.notify_all();
+
+        let guard = mutex.lock().await;
+        let result =
+            compio::time::timeout(std::time::Duration::from_millis(50), cv.wait_guard(guard)).await;
+        assert!(
+            result.is_err(),
+            "wait_guard completed for an old notification"
+        );
+        assert!(!mutex.is_locked(), "cancelled wait must not hold the lock");
+    }
+
+    /// A state change + notify during registration must be caught by the
+    /// predicate re-check
+    #[compio::test]
+    async fn test_mock_wait_until_change_during_registration() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let cv = Arc::new(CondvarGeneric::<MockWaiterQueue>::new());
+            let ready = Arc::new(AtomicBool::new(false));
+
+            let (cv_clone, ready_clone) = (cv.clone(), ready.clone());
+            cv.inner.predicate_waiters.set_on_add_waiter(move || {
+                ready_clone.store(true, Ordering::Release);
+                cv_clone.notify_one();
+            });
+
+            compio::time::timeout(
+                std::time::Duration::from_millis(500),
+                cv.wait_until(|| ready.load(Ordering::Acquire)),
+            )
+            .await
+            .expect("predicate re-check should catch the change");
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    #[compio::test]
+    async fn test_wait_until_needs_predicate() {
+        let cv = Condvar::new();
+        cv.notify_all();
+
+        // Notified, but the predicate is false: keep waiting
+        assert!(
+            !cv.wait_until_timeout(|| false, std::time::Duration::from_millis(20))
+                .await
+        );
+        // Already satisfied: no waiting at all
+        assert!(
+            cv.wait_while_timeout(|| false, std::time::Duration::ZERO)
+                .await
+        );
+    }
+
+    /// `notify_one()` must not be consumed by a predicate waiter
+    #[compio::test]
+    async fn test_predicate_waiter_does_not_steal_notify_one() {
+        let cv = Arc::new(Condvar::new());
+
+        let predicate_waiter = {
+            let cv = cv.clone();
+            compio::runtime::spawn(async move {
+                compio::time::timeout(
+                    std::time::Duration::from_millis(50),
+                    cv.wait_until(|| false),
+                )
+                .await
+                .is_err()
+            })
+        };
+        let plain_waiter = {
+            let cv = cv.clone();
+            compio::runtime::spawn(async move { cv.wait().await })
+        };
+        compio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        cv.notify_one();
+        compio::time::timeout(std::time::Duration::from_millis(500), plain_waiter)
+            .await
+            .expect("notify_one was swallowed")
+            .unwrap();
+        assert!(predicate_waiter.await.unwrap());
+    }
+
     /// Test MockWaiterQueue delegates correctly for normal Condvar operations
     #[compio::test]
     async fn test_mock_condvar_normal_operation() {
         compio::time::timeout(std::time::Duration::from_secs(2), async {
             let cv = Arc::new(CondvarGeneric::<MockWaiterQueue>::new());
 
-            // Normal notify before wait (no hook)
+            // Notify a registered waiter (no hook)
+            let waiter = {
+                let cv = cv.clone();
+                compio::runtime::spawn(async move { cv.wait().await })
+            };
+            compio::time::sleep(std::time::Duration::from_millis(10)).await;
             cv.notify_one();
-            cv.wait().await;
+            waiter.await.unwrap();
 
             // Notify all
+            let waiters: Vec<_> = (0..2)
+                .map(|_| {
+                    let cv = cv.clone();
+                    compio::runtime::spawn(async move { cv.wait().await })
+                })
+                .collect();
+            compio::time::sleep(std::time::Duration::from_millis(10)).await;
             cv.notify_all();
-            cv.wait().await;
-            cv.wait().await;
-
-            // Clear works
-            cv.clear();
+            for waiter in waiters {
+                waiter.await.unwrap();
+            }
         })
         .await
         .expect("Test timed out");
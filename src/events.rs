@@ -0,0 +1,332 @@
+//! Manual-reset and auto-reset events
+//!
+//! This module provides Windows-style events built on the crate's
+//! [`WaiterQueue`](crate::WaiterQueue):
+//!
+//! - [`ManualResetEvent`]: once [`set`](ManualResetEventGeneric::set), every
+//!   current and future waiter is released until the event is
+//!   [`reset`](ManualResetEventGeneric::reset).
+//! - [`AutoResetEvent`]: each `set()` releases exactly one waiter, and the event
+//!   resets itself as that waiter is released. With no waiter, the event stays
+//!   set until the next `wait()`. Repeated `set()` calls do not accumulate.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::ManualResetEvent;
+//! use std::sync::Arc;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let ready = Arc::new(ManualResetEvent::new(false));
+//!
+//!     let mut handles = Vec::new();
+//!     for i in 0..4 {
+//!         let ready = ready.clone();
+//!         handles.push(compio::runtime::spawn(async move {
+//!             ready.wait().await;
+//!             println!("worker {} started", i);
+//!         }));
+//!     }
+//!
+//!     // Release every worker at once
+//!     ready.set();
+//!     for handle in handles {
+//!         handle.await.unwrap();
+//!     }
+//! }
+//! ```
+
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Bit of `ManualResetEventGeneric::state` holding the signalled flag
+const SET: usize = 1;
+
+/// An event that stays signalled until explicitly reset
+///
+/// Generic over the waiter queue implementation; use the [`ManualResetEvent`]
+/// alias.
+///
+/// A `set()` immediately followed by `reset()` still releases every task that
+/// was waiting when `set()` was called.
+pub struct ManualResetEventGeneric<W: WaiterQueueTrait> {
+    /// Lowest bit: signalled. Remaining bits: number of `set()` transitions, so
+    /// a waiter can tell that a set happened even if it was reset already
+    state: AtomicUsize,
+    waiters: W,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type ManualResetEvent = ManualResetEventGeneric<WaiterQueue>;
+
+impl<W: WaiterQueueTrait> ManualResetEventGeneric<W> {
+    /// Create a new event, initially signalled if `set` is true
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::ManualResetEvent;
+    ///
+    /// let event = ManualResetEvent::new(false);
+    /// assert!(!event.is_set());
+    /// ```
+    #[must_use]
+    pub fn new(set: bool) -> Self {
+        Self {
+            state: AtomicUsize::new(if set { SET } else { 0 }),
+            waiters: W::new(),
+        }
+    }
+
+    /// Signal the event, releasing all waiters until [`reset`](Self::reset)
+    ///
+    /// Has no effect if the event is already set.
+    pub fn set(&self) {
+        let transitioned = self
+            .state
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |state| {
+                (state & SET == 0).then(|| state.wrapping_add(2) | SET)
+            })
+            .is_ok();
+        if transitioned {
+            self.waiters.wake_all();
+        }
+    }
+
+    /// Clear the event so later `wait()` calls block
+    pub fn reset(&self) {
+        self.state.fetch_and(!SET, Ordering::AcqRel);
+    }
+
+    /// Check whether the event is currently set
+    pub fn is_set(&self) -> bool {
+        self.state.load(Ordering::Acquire) & SET != 0
+    }
+
+    /// Wait until the event is set
+    ///
+    /// Returns immediately if it is already set.
+    pub async fn wait(&self) {
+        let observed = self.state.load(Ordering::Acquire);
+        if observed & SET != 0 {
+            return;
+        }
+
+        // Reset never changes an unset state, so any change means a set()
+        // happened after we looked, even if the event was reset since
+        let state = &self.state;
+        while state.load(Ordering::Acquire) == observed {
+            // CRITICAL: re-check during registration so a set() between the
+            // load above and registering is not missed
+            self.waiters
+                .add_waiter_if(|| state.load(Ordering::Acquire) != observed)
+                .await;
+        }
+    }
+}
+
+impl<W: WaiterQueueTrait> Default for ManualResetEventGeneric<W> {
+    /// Create an unset event
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl<W: WaiterQueueTrait> fmt::Debug for ManualResetEventGeneric<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManualResetEvent")
+            .field("set", &self.is_set())
+            .finish()
+    }
+}
+
+/// An event that releases a single waiter per `set()` and then resets
+///
+/// Generic over the waiter queue implementation; use the [`AutoResetEvent`]
+/// alias.
+///
+/// # Fairness
+///
+/// `set()` wakes one queued waiter, but a task calling `wait()` at that moment
+/// may consume the signal first. The woken waiter then re-queues.
+pub struct AutoResetEventGeneric<W: WaiterQueueTrait> {
+    /// Whether a signal is waiting to be consumed
+    set: AtomicBool,
+    waiters: W,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type AutoResetEvent = AutoResetEventGeneric<WaiterQueue>;
+
+impl<W: WaiterQueueTrait> AutoResetEventGeneric<W> {
+    /// Create a new event, initially signalled if `set` is true
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::AutoResetEvent;
+    ///
+    /// let event = AutoResetEvent::new(true);
+    /// assert!(event.is_set());
+    /// ```
+    #[must_use]
+    pub fn new(set: bool) -> Self {
+        Self {
+            set: AtomicBool::new(set),
+            waiters: W::new(),
+        }
+    }
+
+    /// Signal the event, releasing exactly one waiter
+    ///
+    /// If nobody is waiting, the event stays set until the next `wait()`. Has
+    /// no effect if the event is already set.
+    pub fn set(&self) {
+        if !self.set.swap(true, Ordering::AcqRel) {
+            self.waiters.wake_one();
+        }
+    }
+
+    /// Clear a signal that has not been consumed yet
+    pub fn reset(&self) {
+        self.set.store(false, Ordering::Release);
+    }
+
+    /// Check whether a signal is waiting to be consumed
+    pub fn is_set(&self) -> bool {
+        self.set.load(Ordering::Acquire)
+    }
+
+    /// Consume the signal without waiting, returning whether it was set
+    pub fn try_wait(&self) -> bool {
+        self.set
+            .compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Wait until the event is set, consuming the signal
+    pub async fn wait(&self) {
+        if self.try_wait() {
+            return;
+        }
+
+        // Forwards a wake we may have consumed if this future is dropped
+        let _waiting = Waiting { event: self };
+        loop {
+            // CRITICAL: check the flag during registration so a set() between
+            // the failed try_wait() and registering is not missed
+            let set = &self.set;
+            self.waiters
+                .add_waiter_if(|| set.load(Ordering::Acquire))
+                .await;
+
+            if self.try_wait() {
+                return;
+            }
+        }
+    }
+}
+
+impl<W: WaiterQueueTrait> Default for AutoResetEventGeneric<W> {
+    /// Create an unset event
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl<W: WaiterQueueTrait> fmt::Debug for AutoResetEventGeneric<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoResetEvent")
+            .field("set", &self.is_set())
+            .finish()
+    }
+}
+
+/// Guard held by a task waiting in `AutoResetEvent::wait()`
+///
+/// If the wait is dropped after being woken but before consuming the signal,
+/// the `wake_one()` from `set()` would be lost while the event stays set. On
+/// drop, wake another waiter if the event is still set.
+struct Waiting<'a, W: WaiterQueueTrait> {
+    event: &'a AutoResetEventGeneric<W>,
+}
+
+impl<'a, W: WaiterQueueTrait> Drop for Waiting<'a, W> {
+    fn drop(&mut self) {
+        if self.event.is_set() {
+            self.event.waiters.wake_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_manual_set_reset() {
+        let event = ManualResetEvent::new(false);
+        event.set();
+        event.set();
+        assert!(event.is_set());
+        event.reset();
+        assert!(!event.is_set());
+    }
+
+    #[test]
+    fn test_auto_try_wait_consumes() {
+        let event = AutoResetEvent::default();
+        assert!(!event.try_wait());
+
+        // Signals do not accumulate
+        event.set();
+        event.set();
+        assert!(event.try_wait());
+        assert!(!event.try_wait());
+    }
+
+    #[compio::test]
+    async fn test_manual_set_then_reset_releases_waiter() {
+        let event = Arc::new(ManualResetEvent::new(false));
+        let waiter = {
+            let event = event.clone();
+            compio::runtime::spawn(async move { event.wait().await })
+        };
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        // The waiter runs only after the reset, but the set still counts
+        event.set();
+        event.reset();
+        compio::time::timeout(Duration::from_millis(500), waiter)
+            .await
+            .expect("pulse was missed")
+            .unwrap();
+    }
+
+    #[compio::test]
+    async fn test_auto_releases_one_waiter_per_set() {
+        let event = Arc::new(AutoResetEvent::new(false));
+        let released = Arc::new(AtomicUsize::new(0));
+        for _ in 0..3 {
+            let event = event.clone();
+            let released = released.clone();
+            compio::runtime::spawn(async move {
+                event.wait().await;
+                released.fetch_add(1, Ordering::SeqCst);
+            })
+            .detach();
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        for expected in 1..=3 {
+            event.set();
+            compio::time::sleep(Duration::from_millis(10)).await;
+            assert_eq!(released.load(Ordering::SeqCst), expected);
+            assert!(!event.is_set());
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Countdown latch and wait group
+//!
+//! This module provides two ways to wait for a set of tasks to finish without
+//! collecting their `JoinHandle`s:
+//!
+//! - [`CountDownLatch`]: starts at a fixed count; [`count_down`] decrements it
+//!   and [`wait`] completes once it reaches zero.
+//! - [`WaitGroup`]: each clone is a member and dropping a member marks it done;
+//!   [`WaitGroup::wait`] completes once every other member has been dropped.
+//!
+//! The decrement that reaches zero wakes every waiter with a single
+//! `wake_all()`.
+//!
+//! [`count_down`]: CountDownLatchGeneric::count_down
+//! [`wait`]: CountDownLatchGeneric::wait
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::WaitGroup;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let wg = WaitGroup::new();
+//!
+//!     for i in 0..10 {
+//!         let member = wg.clone();
+//!         compio::runtime::spawn(async move {
+//!             println!("task {}", i);
+//!             drop(member);
+//!         })
+//!         .detach();
+//!     }
+//!
+//!     // Completes after all ten tasks dropped their member
+//!     wg.wait().await;
+//! }
+//! ```
+
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A counter that tasks can wait on until it reaches zero
+///
+/// Generic over the waiter queue implementation; use the [`CountDownLatch`]
+/// alias. The latch is single-use: once the count reaches zero it stays there.
+pub struct CountDownLatchGeneric<W: WaiterQueueTrait> {
+    count: AtomicUsize,
+    waiters: W,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type CountDownLatch = CountDownLatchGeneric<WaiterQueue>;
+
+impl<W: WaiterQueueTrait> CountDownLatchGeneric<W> {
+    /// Create a latch that opens after `count` calls to `count_down()`
+    ///
+    /// A latch created with a count of 0 is already open.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::CountDownLatch;
+    ///
+    /// let latch = CountDownLatch::new(2);
+    /// latch.count_down();
+    /// assert_eq!(latch.count(), 1);
+    /// ```
+    #[must_use]
+    pub fn new(count: usize) -> Self {
+        Self {
+            count: AtomicUsize::new(count),
+            waiters: W::new(),
+        }
+    }
+
+    /// Decrement the count, waking all waiters if it reaches zero
+    ///
+    /// Has no effect once the count is zero.
+    pub fn count_down(&self) {
+        let previous = self
+            .count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                count.checked_sub(1)
+            });
+        if previous == Ok(1) {
+            self.waiters.wake_all();
+        }
+    }
+
+    /// Increment the count; only valid while it is non-zero
+    fn count_up(&self) {
+        let previous = self.count.fetch_add(1, Ordering::Relaxed);
+        debug_assert!(previous > 0, "count_up on an open latch");
+    }
+
+    /// Get the current count
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Wait until the count reaches zero
+    ///
+    /// Returns immediately if it already has.
+    pub async fn wait(&self) {
+        let count = &self.count;
+        while count.load(Ordering::Acquire) != 0 {
+            // CRITICAL: re-check during registration so the final count_down()
+            // between the check above and registering is not missed
+            self.waiters
+                .add_waiter_if(|| count.load(Ordering::Acquire) == 0)
+                .await;
+        }
+    }
+}
+
+impl<W: WaiterQueueTrait> fmt::Debug for CountDownLatchGeneric<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountDownLatch")
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
+/// Waits for a dynamic group of tasks to finish
+///
+/// Generic over the waiter queue implementation; use the [`WaitGroup`] alias.
+///
+/// Every `WaitGroup` value is a member of the group: cloning adds a member and
+/// dropping one marks it done. [`wait`](Self::wait) consumes the caller's own
+/// member and completes once all other members are dropped.
+pub struct WaitGroupGeneric<W: WaiterQueueTrait> {
+    latch: Arc<CountDownLatchGeneric<W>>,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type WaitGroup = WaitGroupGeneric<WaiterQueue>;
+
+impl<W: WaiterQueueTrait> WaitGroupGeneric<W> {
+    /// Create a group whose only member is the returned value
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            latch: Arc::new(CountDownLatchGeneric::new(1)),
+        }
+    }
+
+    /// Drop this member and wait for every other member to be dropped
+    pub async fn wait(self) {
+        let latch = self.latch.clone();
+        drop(self);
+        latch.wait().await;
+    }
+
+    /// Get the number of members still alive, including this one
+    pub fn count(&self) -> usize {
+        self.latch.count()
+    }
+}
+
+impl<W: WaiterQueueTrait> Default for WaitGroupGeneric<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: WaiterQueueTrait> Clone for WaitGroupGeneric<W> {
+    /// Add a member to the group
+    fn clone(&self) -> Self {
+        // This member keeps the count above zero
+        self.latch.count_up();
+        Self {
+            latch: self.latch.clone(),
+        }
+    }
+}
+
+impl<W: WaiterQueueTrait> Drop for WaitGroupGeneric<W> {
+    /// Mark this member done
+    fn drop(&mut self) {
+        self.latch.count_down();
+    }
+}
+
+impl<W: WaiterQueueTrait> fmt::Debug for WaitGroupGeneric<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitGroup")
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_down_saturates() {
+        let latch = CountDownLatch::new(1);
+        latch.count_down();
+        latch.count_down();
+        assert_eq!(latch.count(), 0);
+    }
+
+    #[test]
+    fn test_wait_group_counts_members() {
+        let wg = WaitGroup::new();
+        let a = wg.clone();
+        let b = a.clone();
+        assert_eq!(wg.count(), 3);
+
+        drop(a);
+        drop(b);
+        assert_eq!(wg.count(), 1);
+    }
+
+    #[compio::test]
+    async fn test_open_latch_does_not_wait() {
+        CountDownLatch::new(0).wait().await;
+        WaitGroup::new().wait().await;
+    }
+}
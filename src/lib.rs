@@ -6,7 +6,24 @@
 //! # Primitives
 //!
 //! - [`Semaphore`] - Async semaphore for bounding concurrency
+//! - [`Barrier`] - Reusable barrier with leader election
 //! - [`Condvar`] - Async condition variable for task notification
+//! - [`CountDownLatch`] / [`WaitGroup`] - Wait for a group of tasks to finish
+//! - [`Mutex`] - Async mutex whose guard can be held across `.await`
+//! - [`RwLock`] - Async reader-writer lock with writer preference
+//! - [`ManualResetEvent`] / [`AutoResetEvent`] - Windows-style signalled events
+//! - [`Notify`] - Edge-triggered notification with single-permit semantics
+//! - [`OnceCell`] / [`Lazy`] - One-time async initialisation
+//! - [`AdaptiveLimiter`] - Concurrency limiter that adjusts its limit from feedback
+//! - [`RateLimiter`] - Token-bucket limiter for operations or bytes per second
+//!
+//! # Channels
+//!
+//! - [`broadcast`] - Every receiver sees every message; slow receivers lag
+//! - [`mpsc`] - Multi-producer, single-consumer queues (bounded with backpressure,
+//!   or unbounded with a lock-free `send`)
+//! - [`oneshot`] - Send a single value from one task to another
+//! - [`watch`] - Latest-value cell; receivers wait for changes
 //!
 //! # Example
 //!
@@ -22,21 +39,49 @@
 //!     for i in 0..1000 {
 //!         let sem = sem.clone();
 //!         compio::runtime::spawn(async move {
-//!             let _permit = sem.acquire().await;
+//!             let _permit = sem.acquire().await.unwrap();
 //!             println!("Task {}", i);
 //!         });
 //!     }
 //! }
 //! ```
 
+mod adaptive_limiter;
+mod barrier;
 mod condvar;
+mod events;
+mod latch;
+mod mutex;
+mod notify;
+mod once_cell;
+mod rate_limiter;
+mod rwlock;
 mod semaphore;
 
+// Channels
+pub mod broadcast;
+pub mod mpsc;
+pub mod oneshot;
+pub mod watch;
+
 // Platform-specific waiter queue implementation
 mod waiter_queue;
 
 // Expose WaiterQueue for testing
 pub use waiter_queue::{WaiterQueue, WaiterQueueTrait};
 
-pub use condvar::Condvar;
-pub use semaphore::{Semaphore, SemaphorePermit};
+pub use adaptive_limiter::{AdaptiveLimiter, LimitAlgorithm, LimiterPermit, Outcome};
+pub use barrier::{Barrier, BarrierWaitResult};
+pub use condvar::{Condvar, CondvarGuard};
+pub use events::{AutoResetEvent, ManualResetEvent};
+pub use latch::{CountDownLatch, WaitGroup};
+pub use mutex::{MappedMutexGuard, Mutex, MutexGuard, OwnedMutexGuard, TryLockError};
+pub use notify::{Notified, Notify};
+pub use once_cell::{Lazy, OnceCell};
+pub use rate_limiter::{RateLimited, RateLimiter};
+pub use rwlock::{
+    OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+pub use semaphore::{
+    AcquireError, AcquireTimeout, OwnedSemaphorePermit, Semaphore, SemaphorePermit, TryAcquireError,
+};
@@ -0,0 +1,447 @@
+//! Bounded channel with semaphore-enforced capacity
+
+use super::{SendError, TryRecvError, TrySendError};
+use crate::semaphore::{Semaphore, TryAcquireError};
+use crate::waiter_queue::WaiterQueue;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Create a bounded channel buffering at most `capacity` messages
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+///
+/// # Example
+///
+/// ```rust
+/// use compio_sync::mpsc;
+///
+/// let (tx, mut rx) = mpsc::channel(1);
+/// tx.try_send(1).unwrap();
+/// assert!(tx.try_send(2).is_err());
+/// assert_eq!(rx.try_recv(), Ok(1));
+/// ```
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "mpsc bounded channel requires capacity > 0");
+    let chan = Arc::new(Chan {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        queued: AtomicUsize::new(0),
+        semaphore: Semaphore::new(capacity),
+        capacity,
+        senders: AtomicUsize::new(1),
+        reserved: AtomicUsize::new(0),
+        rx_waiters: WaiterQueue::new(),
+    });
+    (Sender { chan: chan.clone() }, Receiver { chan })
+}
+
+/// State shared by the senders and the receiver
+///
+/// Every buffered message and every outstanding [`Permit`] holds one semaphore
+/// permit, released when the message is received or the permit dropped. The
+/// semaphore is closed when the receiver closes, which fails pending and later
+/// sends.
+struct Chan<T> {
+    queue: Mutex<VecDeque<T>>,
+    /// Mirrors `queue.len()` so the receiver's wait condition needs no lock
+    queued: AtomicUsize,
+    semaphore: Semaphore,
+    capacity: usize,
+    /// Live `Sender` handles
+    senders: AtomicUsize,
+    /// Live `Permit`s, which may still send after the receiver closes
+    reserved: AtomicUsize,
+    /// The receiver, waiting for a message
+    rx_waiters: WaiterQueue,
+}
+
+impl<T> Chan<T> {
+    /// Buffer a message whose semaphore permit was already taken
+    fn push(&self, value: T) {
+        {
+            let mut queue = self.queue.lock();
+            queue.push_back(value);
+            self.queued.fetch_add(1, Ordering::SeqCst);
+        }
+        self.rx_waiters.wake_one();
+    }
+
+    /// Take the oldest message, returning its permit
+    fn pop(&self) -> Option<T> {
+        let value = {
+            let mut queue = self.queue.lock();
+            let value = queue.pop_front()?;
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            value
+        };
+        self.semaphore.release(1);
+        Some(value)
+    }
+
+    /// Whether no further message can be buffered
+    fn disconnected(senders: &AtomicUsize, semaphore: &Semaphore, reserved: &AtomicUsize) -> bool {
+        (senders.load(Ordering::SeqCst) == 0 || semaphore.is_closed())
+            && reserved.load(Ordering::SeqCst) == 0
+    }
+
+    /// Claim room for one message
+    fn reserve(&self) -> Permit<'_, T> {
+        self.reserved.fetch_add(1, Ordering::SeqCst);
+        Permit { chan: self }
+    }
+}
+
+/// Sending half of a bounded channel; clone it to add producers
+pub struct Sender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send a message, waiting while the channel is full
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`] with the message if the receiver is closed, either
+    /// before the call or while waiting for room.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.reserve().await {
+            Ok(permit) => {
+                permit.send(value);
+                Ok(())
+            }
+            Err(_) => Err(SendError(value)),
+        }
+    }
+
+    /// Send a message if there is room right now
+    ///
+    /// # Errors
+    ///
+    /// - [`TrySendError::Full`] if the channel is at capacity
+    /// - [`TrySendError::Closed`] if the receiver is closed
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        match self.try_reserve() {
+            Ok(permit) => {
+                permit.send(value);
+                Ok(())
+            }
+            Err(TrySendError::Full(())) => Err(TrySendError::Full(value)),
+            Err(TrySendError::Closed(())) => Err(TrySendError::Closed(value)),
+        }
+    }
+
+    /// Wait for room for one message without sending it yet
+    ///
+    /// The returned [`Permit`] guarantees the next [`Permit::send`] succeeds
+    /// immediately, so a producer can secure capacity before building an
+    /// expensive message. Dropping the permit gives the room back.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`] if the receiver is closed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::mpsc;
+    ///
+    /// # async fn example(tx: mpsc::Sender<Vec<u8>>) {
+    /// if let Ok(permit) = tx.reserve().await {
+    ///     let buf = vec![0u8; 64 * 1024]; // only allocated once there is room
+    ///     permit.send(buf);
+    /// }
+    /// # }
+    /// ```
+    pub async fn reserve(&self) -> Result<Permit<'_, T>, SendError<()>> {
+        match self.chan.semaphore.acquire().await {
+            Ok(permit) => {
+                // The permit now belongs to the reserved slot (and then the
+                // message); the receiver releases it
+                std::mem::forget(permit);
+                Ok(self.chan.reserve())
+            }
+            Err(_) => Err(SendError(())),
+        }
+    }
+
+    /// Claim room for one message if there is room right now
+    ///
+    /// # Errors
+    ///
+    /// - [`TrySendError::Full`] if the channel is at capacity
+    /// - [`TrySendError::Closed`] if the receiver is closed
+    pub fn try_reserve(&self) -> Result<Permit<'_, T>, TrySendError<()>> {
+        match self.chan.semaphore.try_acquire() {
+            Ok(permit) => {
+                std::mem::forget(permit);
+                Ok(self.chan.reserve())
+            }
+            Err(TryAcquireError::NoPermits) => Err(TrySendError::Full(())),
+            Err(TryAcquireError::Closed) => Err(TrySendError::Closed(())),
+        }
+    }
+
+    /// Check whether the receiver is closed
+    pub fn is_closed(&self) -> bool {
+        self.chan.semaphore.is_closed()
+    }
+
+    /// Get the number of messages that could be sent right now without waiting
+    pub fn capacity(&self) -> usize {
+        self.chan.semaphore.available_permits()
+    }
+
+    /// Get the capacity the channel was created with
+    pub fn max_capacity(&self) -> usize {
+        self.chan.capacity
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.chan.senders.fetch_add(1, Ordering::SeqCst);
+        Self {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.chan.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Last sender: let the receiver observe the disconnect
+            self.chan.rx_waiters.wake_one();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.capacity())
+            .field("max_capacity", &self.max_capacity())
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// Room for one message, obtained from [`Sender::reserve`]
+///
+/// Dropping it without sending returns the room to the channel.
+pub struct Permit<'a, T> {
+    chan: &'a Chan<T>,
+}
+
+impl<'a, T> Permit<'a, T> {
+    /// Send a message using the reserved room; never waits or fails
+    ///
+    /// If the receiver was dropped meanwhile, the message is dropped with the
+    /// channel.
+    pub fn send(self, value: T) {
+        let chan = self.chan;
+        std::mem::forget(self);
+        // The message keeps the semaphore permit. Queue it before dropping the
+        // reservation so the receiver cannot see the channel as drained
+        chan.push(value);
+        chan.reserved.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl<'a, T> Drop for Permit<'a, T> {
+    fn drop(&mut self) {
+        self.chan.reserved.fetch_sub(1, Ordering::SeqCst);
+        self.chan.semaphore.release(1);
+        if self.chan.semaphore.is_closed() {
+            // A closed receiver may be waiting for the last permit to go
+            self.chan.rx_waiters.wake_one();
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for Permit<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Permit").finish_non_exhaustive()
+    }
+}
+
+/// Receiving half of a bounded channel
+pub struct Receiver<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive the next message, waiting until one is sent
+    ///
+    /// Returns `None` once the channel is closed (every sender dropped, or
+    /// [`close`](Self::close) called) and all buffered messages were received.
+    pub async fn recv(&mut self) -> Option<T> {
+        let chan = &*self.chan;
+        loop {
+            if let Some(value) = chan.pop() {
+                return Some(value);
+            }
+            let (queued, senders, semaphore, reserved) =
+                (&chan.queued, &chan.senders, &chan.semaphore, &chan.reserved);
+            if Chan::<T>::disconnected(senders, semaphore, reserved) {
+                // A last message may have landed after the pop above
+                return chan.pop();
+            }
+
+            // CRITICAL: check during registration so a send or the last sender
+            // dropping between the checks above and registering is not missed
+            chan.rx_waiters
+                .add_waiter_if(|| {
+                    queued.load(Ordering::SeqCst) > 0
+                        || Chan::<T>::disconnected(senders, semaphore, reserved)
+                })
+                .await;
+        }
+    }
+
+    /// Receive up to `limit` messages into `buffer`, waiting for at least one
+    ///
+    /// Returns the number of messages appended; 0 means the channel is closed
+    /// and drained (or `limit` is 0).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::mpsc;
+    ///
+    /// # async fn example(mut rx: mpsc::Receiver<u64>) {
+    /// let mut batch = Vec::with_capacity(32);
+    /// while rx.recv_many(&mut batch, 32).await > 0 {
+    ///     println!("flushing {} items", batch.len());
+    ///     batch.clear();
+    /// }
+    /// # }
+    /// ```
+    pub async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        let Some(first) = self.recv().await else {
+            return 0;
+        };
+        buffer.push(first);
+
+        let mut received = 1;
+        while received < limit {
+            match self.chan.pop() {
+                Some(value) => buffer.push(value),
+                None => break,
+            }
+            received += 1;
+        }
+        received
+    }
+
+    /// Receive a message if one is buffered
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if no message is buffered right now
+    /// - [`TryRecvError::Disconnected`] if the channel is closed and drained
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        if let Some(value) = self.chan.pop() {
+            return Ok(value);
+        }
+        let chan = &*self.chan;
+        if Chan::<T>::disconnected(&chan.senders, &chan.semaphore, &chan.reserved) {
+            chan.pop().ok_or(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Close the channel without dropping the receiver
+    ///
+    /// Pending and later sends fail, while messages already buffered (or sent
+    /// through an outstanding [`Permit`]) can still be received.
+    pub fn close(&mut self) {
+        self.chan.semaphore.close();
+    }
+
+    /// Get the number of buffered messages
+    pub fn len(&self) -> usize {
+        self.chan.queued.load(Ordering::SeqCst)
+    }
+
+    /// Check whether no message is buffered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+        // Drop buffered messages now rather than when the last sender goes
+        while self.chan.pop().is_some() {}
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacity_returns_on_recv() {
+        let (tx, mut rx) = channel(2);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(tx.capacity(), 0);
+        assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(tx.capacity(), 1);
+        assert_eq!(tx.max_capacity(), 2);
+    }
+
+    #[test]
+    fn test_dropped_permit_returns_capacity() {
+        let (tx, _rx) = channel::<u8>(1);
+        let permit = tx.try_reserve().unwrap();
+        assert!(tx.try_reserve().is_err());
+        drop(permit);
+        assert!(tx.try_reserve().is_ok());
+    }
+
+    #[test]
+    fn test_close_keeps_buffered_and_reserved() {
+        let (tx, mut rx) = channel(3);
+        tx.try_send(1).unwrap();
+        let permit = tx.try_reserve().unwrap();
+
+        rx.close();
+        assert_eq!(tx.try_send(9), Err(TrySendError::Closed(9)));
+        assert_eq!(rx.try_recv(), Ok(1));
+        // The outstanding permit can still send
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        permit.send(2);
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_receiver_drop_drops_messages() {
+        let value = Arc::new(());
+        let (tx, rx) = channel(1);
+        tx.try_send(value.clone()).unwrap();
+        drop(rx);
+        assert_eq!(Arc::strong_count(&value), 1);
+        assert!(tx.is_closed());
+    }
+}
@@ -0,0 +1,129 @@
+//! Multi-producer, single-consumer channels
+//!
+//! [`channel(cap)`](channel) creates a bounded channel: at most `cap` messages
+//! are buffered, and [`Sender::send`] waits for room. Capacity is enforced by
+//! the crate's [`Semaphore`](crate::Semaphore), so producers get backpressure
+//! instead of an unbounded queue, e.g. pausing directory discovery while the
+//! copy workers are saturated.
+//!
+//! Senders are cloned to add producers. The channel closes when every sender
+//! is dropped or the receiver calls [`Receiver::close`]; messages already
+//! buffered can still be received.
+//!
+//! [`unbounded_channel()`] creates a channel without a capacity limit whose
+//! [`UnboundedSender::send`] never waits and never takes a lock, for producers
+//! in synchronous code such as callbacks.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::mpsc;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let (tx, mut rx) = mpsc::channel(16);
+//!
+//!     for worker in 0..4 {
+//!         let tx = tx.clone();
+//!         compio::runtime::spawn(async move {
+//!             for i in 0..100 {
+//!                 // Waits while 16 messages are unread
+//!                 if tx.send((worker, i)).await.is_err() {
+//!                     break;
+//!                 }
+//!             }
+//!         })
+//!         .detach();
+//!     }
+//!     drop(tx);
+//!
+//!     while let Some((worker, i)) = rx.recv().await {
+//!         println!("worker {} item {}", worker, i);
+//!     }
+//! }
+//! ```
+
+mod bounded;
+mod unbounded;
+
+pub use bounded::{channel, Permit, Receiver, Sender};
+pub use unbounded::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use std::fmt;
+
+/// Error returned by `send()` when the receiver is closed, holding the message
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+/// Error returned by `try_send()`, holding the message
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    /// The channel is at capacity
+    Full(T),
+    /// The receiver is closed
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Get the message back
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) | TrySendError::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("Full(..)"),
+            TrySendError::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("channel full"),
+            TrySendError::Closed(_) => f.write_str("channel closed"),
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
+
+/// Error returned by `Receiver::try_recv()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No message is buffered right now
+    Empty,
+    /// No message is buffered and none can arrive: every sender is dropped or
+    /// the receiver was closed
+    Disconnected,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("channel empty"),
+            TryRecvError::Disconnected => f.write_str("channel disconnected"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
@@ -0,0 +1,414 @@
+//! Unbounded channel with a lock-free segmented queue
+//!
+//! Messages live in a linked list of fixed-size blocks. A producer claims a
+//! slot by advancing the shared tail index with a CAS, writes the message and
+//! marks the slot written; the producer that claims the last slot of a block
+//! links in the next one. The single consumer walks the list from its own head
+//! and frees each block once every slot in it was read.
+//!
+//! This is the segment scheme of crossbeam's `SegQueue`, simplified for a
+//! single consumer: blocks need no reader bookkeeping because only the
+//! receiver reads, in order.
+//!
+//! Producers only dereference a block after claiming a slot in it, and the
+//! receiver only frees a block after reading every slot, so no producer can
+//! touch a freed block.
+
+use super::{SendError, TryRecvError};
+use atomic_waker::AtomicWaker;
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::future::poll_fn;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
+
+/// Indices per block, including the one reserved as a transition marker
+const LAP: usize = 32;
+/// Slots per block; a tail offset of `BLOCK_CAP` means the next block is being
+/// linked in
+const BLOCK_CAP: usize = LAP - 1;
+
+/// Create an unbounded channel
+///
+/// Sending never waits and never takes a lock, so it can be called from
+/// synchronous code such as callbacks or other threads.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use compio_sync::mpsc;
+///
+/// #[compio::main]
+/// async fn main() {
+///     let (tx, mut rx) = mpsc::unbounded_channel();
+///
+///     // e.g. a progress callback running on another thread
+///     let worker = std::thread::spawn(move || {
+///         for done in 0..100 {
+///             tx.send(done).unwrap();
+///         }
+///     });
+///
+///     while let Some(done) = rx.recv().await {
+///         println!("{} done", done);
+///     }
+///     worker.join().unwrap();
+/// }
+/// ```
+pub fn unbounded_channel<T>() -> (UnboundedSender<T>, UnboundedReceiver<T>) {
+    let first = Block::alloc();
+    let chan = Arc::new(Chan {
+        tail_index: AtomicUsize::new(0),
+        tail_block: AtomicPtr::new(first),
+        head: UnsafeCell::new(Head {
+            index: 0,
+            block: first,
+        }),
+        senders: AtomicUsize::new(1),
+        rx_closed: AtomicBool::new(false),
+        rx_waker: AtomicWaker::new(),
+    });
+    (
+        UnboundedSender { chan: chan.clone() },
+        UnboundedReceiver { chan },
+    )
+}
+
+/// A slot holding one message
+struct Slot<T> {
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// Set by the producer once `value` is written
+    written: AtomicBool,
+}
+
+/// A segment of the queue
+struct Block<T> {
+    /// Linked by the producer that claims the last slot, before it writes it
+    next: AtomicPtr<Block<T>>,
+    slots: [Slot<T>; BLOCK_CAP],
+}
+
+impl<T> Block<T> {
+    fn alloc() -> *mut Self {
+        Box::into_raw(Box::new(Block {
+            next: AtomicPtr::new(ptr::null_mut()),
+            slots: std::array::from_fn(|_| Slot {
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                written: AtomicBool::new(false),
+            }),
+        }))
+    }
+}
+
+/// Consumer position; only touched by the receiver (and by `Chan::drop`)
+struct Head<T> {
+    index: usize,
+    block: *mut Block<T>,
+}
+
+/// State shared by the senders and the receiver
+struct Chan<T> {
+    /// Next index to claim; moves in step with `tail_block`
+    tail_index: AtomicUsize,
+    /// Block containing `tail_index`
+    tail_block: AtomicPtr<Block<T>>,
+    head: UnsafeCell<Head<T>>,
+    /// Live `UnboundedSender` handles
+    senders: AtomicUsize,
+    /// Set when the receiver is closed or dropped
+    rx_closed: AtomicBool,
+    /// The receiver, parked in `recv()`
+    rx_waker: AtomicWaker,
+}
+
+// SAFETY: messages are moved from producers to the single consumer; blocks are
+// shared through the claim protocol described in the module docs
+unsafe impl<T: Send> Send for Chan<T> {}
+// SAFETY: as above; `head` is only accessed through the unique receiver
+unsafe impl<T: Send> Sync for Chan<T> {}
+
+impl<T> Chan<T> {
+    /// Append a message; never blocks
+    fn push(&self, value: T) {
+        let mut tail = self.tail_index.load(Ordering::Acquire);
+        let mut block = self.tail_block.load(Ordering::Acquire);
+        let mut next_block = None;
+
+        loop {
+            let offset = tail % LAP;
+
+            // Another producer is linking in the next block; it finishes
+            // without waiting on anyone, so this is brief
+            if offset == BLOCK_CAP {
+                std::hint::spin_loop();
+                tail = self.tail_index.load(Ordering::Acquire);
+                block = self.tail_block.load(Ordering::Acquire);
+                continue;
+            }
+
+            // Allocate before claiming the last slot so the transition window
+            // does not include an allocation
+            if offset + 1 == BLOCK_CAP && next_block.is_none() {
+                next_block = Some(Block::alloc());
+            }
+
+            match self.tail_index.compare_exchange_weak(
+                tail,
+                tail + 1,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // SAFETY: the slot at `offset` in `block` is now ours, and
+                    // the receiver cannot free `block` before reading it
+                    unsafe {
+                        if offset + 1 == BLOCK_CAP {
+                            let next_block = next_block.take().expect("allocated above");
+                            self.tail_block.store(next_block, Ordering::Release);
+                            // Skip the transition marker
+                            self.tail_index.fetch_add(1, Ordering::Release);
+                            (*block).next.store(next_block, Ordering::Release);
+                        }
+
+                        let slot = &(*block).slots[offset];
+                        (*slot.value.get()).write(value);
+                        slot.written.store(true, Ordering::Release);
+                    }
+                    break;
+                }
+                Err(current) => {
+                    tail = current;
+                    block = self.tail_block.load(Ordering::Acquire);
+                }
+            }
+        }
+
+        if let Some(unused) = next_block {
+            // SAFETY: never published
+            drop(unsafe { Box::from_raw(unused) });
+        }
+    }
+
+    /// Take the next message if it has been written
+    ///
+    /// # Safety
+    ///
+    /// Only the receiver (or `Drop` with exclusive access) may call this.
+    unsafe fn pop(&self) -> Option<T> {
+        // SAFETY: the caller guarantees exclusive access to the head
+        let head = unsafe { &mut *self.head.get() };
+        let offset = head.index % LAP;
+        // SAFETY: `head.block` is only freed below, after moving past it
+        let slot = unsafe { &(*head.block).slots[offset] };
+
+        // Not claimed yet, or claimed but still being written
+        if !slot.written.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: `written` was set after the value was written; each slot is
+        // read once
+        let value = unsafe { (*slot.value.get()).assume_init_read() };
+
+        if offset + 1 == BLOCK_CAP {
+            // SAFETY: the producer of the last slot linked the next block before
+            // marking it written. Every slot of this block has been read, so no
+            // producer will touch it again.
+            unsafe {
+                let next = (*head.block).next.load(Ordering::Acquire);
+                drop(Box::from_raw(head.block));
+                head.block = next;
+            }
+            head.index += 2;
+        } else {
+            head.index += 1;
+        }
+        Some(value)
+    }
+}
+
+impl<T> Drop for Chan<T> {
+    fn drop(&mut self) {
+        // Every sender is gone, so every claimed slot is written
+        // SAFETY: `&mut self` gives exclusive access
+        while unsafe { self.pop() }.is_some() {}
+        // SAFETY: the head block is the last one still allocated
+        drop(unsafe { Box::from_raw(self.head.get_mut().block) });
+    }
+}
+
+/// Sending half of an unbounded channel; clone it to add producers
+pub struct UnboundedSender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> UnboundedSender<T> {
+    /// Send a message without waiting or locking
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`] with the message if the receiver is closed.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.chan.rx_closed.load(Ordering::Acquire) {
+            return Err(SendError(value));
+        }
+        self.chan.push(value);
+        self.chan.rx_waker.wake();
+        Ok(())
+    }
+
+    /// Check whether the receiver is closed
+    pub fn is_closed(&self) -> bool {
+        self.chan.rx_closed.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        self.chan.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for UnboundedSender<T> {
+    fn drop(&mut self) {
+        // AcqRel so the receiver seeing 0 also sees every message sent
+        if self.chan.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.chan.rx_waker.wake();
+        }
+    }
+}
+
+impl<T> fmt::Debug for UnboundedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnboundedSender")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// Receiving half of an unbounded channel
+pub struct UnboundedReceiver<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> UnboundedReceiver<T> {
+    /// Receive the next message, waiting until one is sent
+    ///
+    /// Returns `None` once every sender is dropped (or the receiver is
+    /// closed) and all messages were received.
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| {
+            match self.try_recv() {
+                Ok(value) => return Poll::Ready(Some(value)),
+                Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            self.chan.rx_waker.register(cx.waker());
+            // CRITICAL: re-check after registering so a send between the check
+            // above and registering is not missed
+            match self.try_recv() {
+                Ok(value) => Poll::Ready(Some(value)),
+                Err(TryRecvError::Disconnected) => Poll::Ready(None),
+                Err(TryRecvError::Empty) => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Receive a message if one is available
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if no message is available right now
+    /// - [`TryRecvError::Disconnected`] if every sender is dropped (or the
+    ///   receiver is closed) and all messages were received
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        // SAFETY: `&mut self` on the unique receiver
+        if let Some(value) = unsafe { self.chan.pop() } {
+            return Ok(value);
+        }
+        if self.chan.senders.load(Ordering::Acquire) == 0
+            || self.chan.rx_closed.load(Ordering::Acquire)
+        {
+            // No new message can be accepted: one last look
+            // SAFETY: as above
+            unsafe { self.chan.pop() }.ok_or(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Close the channel without dropping the receiver
+    ///
+    /// Later sends fail; messages already sent can still be received, after
+    /// which `recv()` returns `None` even while senders are alive.
+    pub fn close(&mut self) {
+        self.chan.rx_closed.store(true, Ordering::Release);
+    }
+}
+
+impl<T> Drop for UnboundedReceiver<T> {
+    fn drop(&mut self) {
+        self.close();
+        // Drop queued messages now rather than when the last sender goes
+        // SAFETY: the receiver still has exclusive access to the head
+        while unsafe { self.chan.pop() }.is_some() {}
+    }
+}
+
+impl<T> fmt::Debug for UnboundedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnboundedReceiver").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fifo_across_blocks() {
+        let (tx, mut rx) = unbounded_channel();
+        for i in 0..(BLOCK_CAP * 3 + 5) {
+            tx.send(i).unwrap();
+        }
+        for i in 0..(BLOCK_CAP * 3 + 5) {
+            assert_eq!(rx.try_recv(), Ok(i));
+        }
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        drop(tx);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_unread_messages_dropped() {
+        let value = Arc::new(());
+        let (tx, rx) = unbounded_channel();
+        for _ in 0..(BLOCK_CAP + 2) {
+            tx.send(value.clone()).unwrap();
+        }
+        drop(rx);
+        assert_eq!(Arc::strong_count(&value), 1);
+        assert!(tx.send(value.clone()).is_err());
+    }
+
+    #[test]
+    fn test_messages_sent_after_receiver_drop_are_freed() {
+        let value = Arc::new(());
+        let (tx, mut rx) = unbounded_channel();
+        tx.send(value.clone()).unwrap();
+        rx.close();
+        // Closed: refused and handed back
+        assert!(tx.send(value.clone()).is_err());
+        assert!(rx.try_recv().is_ok());
+        drop(tx);
+        drop(rx);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
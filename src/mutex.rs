@@ -0,0 +1,510 @@
+//! Asynchronous mutex for compio
+//!
+//! This module provides `Mutex<T>`, an async mutual-exclusion lock whose
+//! `lock()` waits on the crate's [`WaiterQueue`](crate::WaiterQueue) instead of
+//! blocking the thread, so it can be held across `.await` points and uses the
+//! io_uring futex backend on Linux.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::Mutex;
+//! use std::sync::Arc;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let stats = Arc::new(Mutex::new(Vec::new()));
+//!
+//!     let mut handles = Vec::new();
+//!     for i in 0..10 {
+//!         let stats = stats.clone();
+//!         handles.push(compio::runtime::spawn(async move {
+//!             stats.lock().await.push(i);
+//!         }));
+//!     }
+//!     for handle in handles {
+//!         handle.await.unwrap();
+//!     }
+//!
+//!     assert_eq!(stats.lock().await.len(), 10);
+//! }
+//! ```
+
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// An async mutex protecting a value of type `T`
+///
+/// Generic over the waiter queue implementation; use the [`Mutex`] alias.
+///
+/// # Fairness
+///
+/// Unlocking wakes one waiter, but a task calling `lock()` at that moment may
+/// take the lock first (barging). The woken waiter then re-queues. This keeps
+/// the uncontended path to a single CAS.
+pub struct MutexGeneric<T, W: WaiterQueueTrait> {
+    raw: RawMutex<W>,
+    data: UnsafeCell<T>,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type Mutex<T> = MutexGeneric<T, WaiterQueue>;
+
+// SAFETY: access to `data` is serialised by `raw`; moving the mutex moves `T`
+unsafe impl<T: Send, W: WaiterQueueTrait + Send> Send for MutexGeneric<T, W> {}
+// SAFETY: only one guard at a time can reach `data`, so sharing the mutex
+// between threads only ever hands `&mut T` to one of them (hence `T: Send`)
+unsafe impl<T: Send, W: WaiterQueueTrait> Sync for MutexGeneric<T, W> {}
+
+/// Lock state and waiters, shared by all guard types
+pub(crate) struct RawMutex<W: WaiterQueueTrait> {
+    /// Whether a guard currently exists
+    locked: AtomicBool,
+    /// Tasks waiting for the lock
+    waiters: W,
+}
+
+impl<W: WaiterQueueTrait> RawMutex<W> {
+    fn new() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            waiters: W::new(),
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    async fn lock(&self) {
+        if self.try_lock() {
+            return;
+        }
+
+        // Forwards a wake we may have consumed if this future is dropped
+        let _waiting = Waiting { raw: self };
+        loop {
+            // CRITICAL: check the lock state during registration so an unlock
+            // between the failed try_lock() and registering is not missed
+            let locked = &self.locked;
+            self.waiters
+                .add_waiter_if(|| !locked.load(Ordering::Acquire))
+                .await;
+
+            if self.try_lock() {
+                return;
+            }
+        }
+    }
+
+    fn unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+        self.waiters.wake_one();
+    }
+}
+
+/// Guard held by a task waiting in `lock()`
+///
+/// If the lock future is dropped after being woken but before taking the lock,
+/// the `wake_one()` meant to hand the lock over would be lost and the remaining
+/// waiters could sleep forever. On drop, wake another waiter if the lock is
+/// free; a spurious wake is harmless since waiters re-check the state.
+struct Waiting<'a, W: WaiterQueueTrait> {
+    raw: &'a RawMutex<W>,
+}
+
+impl<'a, W: WaiterQueueTrait> Drop for Waiting<'a, W> {
+    fn drop(&mut self) {
+        if !self.raw.locked.load(Ordering::Acquire) {
+            self.raw.waiters.wake_one();
+        }
+    }
+}
+
+impl<T, W: WaiterQueueTrait> MutexGeneric<T, W> {
+    /// Create a new unlocked mutex holding `value`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::Mutex;
+    ///
+    /// let mutex = Mutex::new(0);
+    /// assert!(!mutex.is_locked());
+    /// ```
+    pub fn new(value: T) -> Self {
+        Self {
+            raw: RawMutex::new(),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// Lock the mutex, waiting asynchronously until it is available
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::Mutex;
+    ///
+    /// # async fn example() {
+    /// let mutex = Mutex::new(1);
+    /// *mutex.lock().await += 1;
+    /// assert_eq!(*mutex.lock().await, 2);
+    /// # }
+    /// ```
+    pub async fn lock(&self) -> MutexGuard<'_, T, W> {
+        self.raw.lock().await;
+        MutexGuard { mutex: self }
+    }
+
+    /// Try to lock the mutex without waiting
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if the mutex is currently locked.
+    pub fn try_lock(&self) -> Result<MutexGuard<'_, T, W>, TryLockError> {
+        if self.raw.try_lock() {
+            Ok(MutexGuard { mutex: self })
+        } else {
+            Err(TryLockError::new())
+        }
+    }
+
+    /// Lock the mutex through an `Arc`, returning a `'static` guard
+    ///
+    /// The guard keeps the mutex alive, so it can be moved into spawned tasks.
+    pub async fn lock_owned(self: Arc<Self>) -> OwnedMutexGuard<T, W> {
+        self.raw.lock().await;
+        OwnedMutexGuard { mutex: self }
+    }
+
+    /// Try to lock the mutex through an `Arc` without waiting
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if the mutex is currently locked.
+    pub fn try_lock_owned(self: Arc<Self>) -> Result<OwnedMutexGuard<T, W>, TryLockError> {
+        if self.raw.try_lock() {
+            Ok(OwnedMutexGuard { mutex: self })
+        } else {
+            Err(TryLockError::new())
+        }
+    }
+
+    /// Check whether the mutex is currently locked
+    pub fn is_locked(&self) -> bool {
+        self.raw.locked.load(Ordering::Acquire)
+    }
+
+    /// Get a mutable reference to the value without locking
+    ///
+    /// The `&mut self` borrow guarantees no guards exist.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Consume the mutex, returning the value
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: Default, W: WaiterQueueTrait> Default for MutexGeneric<T, W> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T, W: WaiterQueueTrait> From<T> for MutexGeneric<T, W> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for MutexGeneric<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+        match self.try_lock() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(_) => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// RAII guard for a locked [`Mutex`]; unlocks on drop
+pub struct MutexGuard<'a, T, W: WaiterQueueTrait> {
+    pub(crate) mutex: &'a MutexGeneric<T, W>,
+}
+
+// SAFETY: sharing the guard only hands out `&T`
+unsafe impl<'a, T: Sync, W: WaiterQueueTrait> Sync for MutexGuard<'a, T, W> {}
+
+impl<'a, T, W: WaiterQueueTrait> MutexGuard<'a, T, W> {
+    /// Narrow the guard to a component of the locked value
+    ///
+    /// The mutex stays locked until the returned guard is dropped. This is an
+    /// associated function so it does not shadow methods on `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::{Mutex, MutexGuard};
+    ///
+    /// # async fn example() {
+    /// let mutex = Mutex::new((0u32, String::new()));
+    /// let mut name = MutexGuard::map(mutex.lock().await, |pair| &mut pair.1);
+    /// name.push_str("done");
+    /// # }
+    /// ```
+    pub fn map<U, F>(this: Self, f: F) -> MappedMutexGuard<'a, U, W>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let mutex = this.mutex;
+        // SAFETY: the lock is held by `this`, which is only forgotten once `f`
+        // returned, so a panicking `f` still unlocks on unwind
+        let data = f(unsafe { &mut *mutex.data.get() });
+        // Ownership of the lock moves to the mapped guard
+        std::mem::forget(this);
+        MappedMutexGuard {
+            raw: &mutex.raw,
+            data,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, W: WaiterQueueTrait> Deref for MutexGuard<'a, T, W> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: this guard holds the lock
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<'a, T, W: WaiterQueueTrait> DerefMut for MutexGuard<'a, T, W> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: this guard holds the lock
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<'a, T, W: WaiterQueueTrait> Drop for MutexGuard<'a, T, W> {
+    fn drop(&mut self) {
+        self.mutex.raw.unlock();
+    }
+}
+
+impl<'a, T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for MutexGuard<'a, T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Owned RAII guard for a locked [`Mutex`], obtained through an `Arc`
+///
+/// Holds a clone of the `Arc`, so it is `'static` and can be moved into spawned
+/// tasks.
+pub struct OwnedMutexGuard<T, W: WaiterQueueTrait> {
+    mutex: Arc<MutexGeneric<T, W>>,
+}
+
+// SAFETY: sharing the guard only hands out `&T`
+unsafe impl<T: Sync, W: WaiterQueueTrait> Sync for OwnedMutexGuard<T, W> {}
+
+impl<T, W: WaiterQueueTrait> OwnedMutexGuard<T, W> {
+    /// Get the mutex this guard locks
+    pub fn mutex(this: &Self) -> &Arc<MutexGeneric<T, W>> {
+        &this.mutex
+    }
+}
+
+impl<T, W: WaiterQueueTrait> Deref for OwnedMutexGuard<T, W> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: this guard holds the lock
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T, W: WaiterQueueTrait> DerefMut for OwnedMutexGuard<T, W> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: this guard holds the lock
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T, W: WaiterQueueTrait> Drop for OwnedMutexGuard<T, W> {
+    fn drop(&mut self) {
+        self.mutex.raw.unlock();
+    }
+}
+
+impl<T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for OwnedMutexGuard<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Guard for a component of a locked [`Mutex`], created by [`MutexGuard::map`]
+pub struct MappedMutexGuard<'a, U, W: WaiterQueueTrait> {
+    raw: &'a RawMutex<W>,
+    data: *mut U,
+    /// Borrows `U` mutably for `'a`, like `&'a mut U`
+    _marker: PhantomData<&'a mut U>,
+}
+
+// SAFETY: the guard behaves like `&'a mut U` plus a reference to the lock
+unsafe impl<'a, U: Send, W: WaiterQueueTrait> Send for MappedMutexGuard<'a, U, W> {}
+// SAFETY: sharing the guard only hands out `&U`
+unsafe impl<'a, U: Sync, W: WaiterQueueTrait> Sync for MappedMutexGuard<'a, U, W> {}
+
+impl<'a, U, W: WaiterQueueTrait> MappedMutexGuard<'a, U, W> {
+    /// Narrow the guard further
+    pub fn map<V, F>(this: Self, f: F) -> MappedMutexGuard<'a, V, W>
+    where
+        F: FnOnce(&mut U) -> &mut V,
+    {
+        let raw = this.raw;
+        // SAFETY: the lock is held by `this`, which is only forgotten once `f`
+        // returned, so a panicking `f` still unlocks on unwind
+        let data = f(unsafe { &mut *this.data });
+        // Ownership of the lock moves to the new guard
+        std::mem::forget(this);
+        MappedMutexGuard {
+            raw,
+            data,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, U, W: WaiterQueueTrait> Deref for MappedMutexGuard<'a, U, W> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        // SAFETY: this guard holds the lock; `data` points into the locked value
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, U, W: WaiterQueueTrait> DerefMut for MappedMutexGuard<'a, U, W> {
+    fn deref_mut(&mut self) -> &mut U {
+        // SAFETY: this guard holds the lock; `data` points into the locked value
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<'a, U, W: WaiterQueueTrait> Drop for MappedMutexGuard<'a, U, W> {
+    fn drop(&mut self) {
+        self.raw.unlock();
+    }
+}
+
+impl<'a, U: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for MappedMutexGuard<'a, U, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Error returned by `try_lock()` / `try_read()` / `try_write()` when the lock
+/// cannot be taken without waiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryLockError(());
+
+impl TryLockError {
+    pub(crate) fn new() -> Self {
+        TryLockError(())
+    }
+}
+
+impl fmt::Display for TryLockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("lock is held")
+    }
+}
+
+impl std::error::Error for TryLockError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_lock_exclusive() {
+        let mutex = Mutex::new(5);
+        let mut guard = mutex.try_lock().unwrap();
+        assert!(mutex.is_locked());
+        assert!(mutex.try_lock().is_err());
+
+        *guard += 1;
+        drop(guard);
+
+        assert!(!mutex.is_locked());
+        assert_eq!(*mutex.try_lock().unwrap(), 6);
+    }
+
+    #[test]
+    fn test_mapped_guard_holds_lock() {
+        let mutex = Mutex::new((1, vec![1, 2]));
+        let mut second = MutexGuard::map(mutex.try_lock().unwrap(), |pair| &mut pair.1);
+        second.push(3);
+        assert!(mutex.try_lock().is_err());
+
+        let mut last = MappedMutexGuard::map(second, |v| v.last_mut().unwrap());
+        *last = 30;
+        assert!(mutex.is_locked());
+        drop(last);
+
+        assert_eq!(mutex.into_inner(), (1, vec![1, 2, 30]));
+    }
+
+    #[test]
+    fn test_owned_guard_keeps_mutex_alive() {
+        let mutex = Arc::new(Mutex::new(String::from("a")));
+        let mut guard = mutex.clone().try_lock_owned().unwrap();
+        drop(mutex);
+
+        guard.push('b');
+        let mutex = OwnedMutexGuard::mutex(&guard).clone();
+        drop(guard);
+        assert_eq!(*mutex.try_lock().unwrap(), "ab");
+    }
+
+    #[compio::test]
+    async fn test_lock_waits_for_unlock() {
+        let mutex = Arc::new(Mutex::new(0));
+        let guard = mutex.try_lock().unwrap();
+
+        let waiter = {
+            let mutex = mutex.clone();
+            compio::runtime::spawn(async move {
+                *mutex.lock().await = 1;
+            })
+        };
+        compio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert_eq!(*guard, 0);
+
+        drop(guard);
+        waiter.await.unwrap();
+        assert_eq!(*mutex.try_lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_debug_shows_locked() {
+        let mutex = Mutex::new(7);
+        assert_eq!(format!("{:?}", mutex), "Mutex { data: 7 }");
+        let _guard = mutex.try_lock().unwrap();
+        assert_eq!(format!("{:?}", mutex), "Mutex { data: <locked> }");
+    }
+}
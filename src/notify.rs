@@ -0,0 +1,471 @@
+//! Edge-triggered task notification with permit semantics
+//!
+//! This module provides `Notify`, modelled on `tokio::sync::Notify`:
+//!
+//! - `notify_one()` wakes exactly one waiting task. If no task is waiting it
+//!   stores a single permit, consumed by the next `notified().await`. Permits do
+//!   not accumulate.
+//! - `notify_waiters()` wakes every task waiting *right now* and stores nothing.
+//! - A [`Notified`] future can be [enabled](Notified::enable) before checking
+//!   shared state, so a `notify_waiters()` issued between the check and the
+//!   `.await` is not missed.
+//!
+//! [`Condvar`](crate::Condvar) shares this machinery but drops a
+//! `notify_one()` that finds no waiter instead of storing a permit.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::Notify;
+//! use std::sync::Arc;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let notify = Arc::new(Notify::new());
+//!     let notify2 = notify.clone();
+//!
+//!     let handle = compio::runtime::spawn(async move {
+//!         notify2.notified().await;
+//!         println!("received notification");
+//!     });
+//!
+//!     notify.notify_one();
+//!     handle.await.unwrap();
+//! }
+//! ```
+
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use parking_lot::Mutex;
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Notifies one task or all waiting tasks
+///
+/// Generic over the waiter queue implementation; use the [`Notify`] alias.
+pub struct NotifyGeneric<W: WaiterQueueTrait> {
+    /// Bookkeeping deciding which waiter a notification belongs to
+    state: Mutex<NotifyState>,
+    /// Whether a `notify_one()` with no waiters is kept as a permit (`Notify`)
+    /// or dropped (`Condvar`)
+    store_permits: bool,
+    /// Parked `Notified` futures
+    pub(crate) waiters: W,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type Notify = NotifyGeneric<WaiterQueue>;
+
+/// Shared notification state
+///
+/// Each enabled waiter gets a registration sequence number, and `notify_one()`
+/// hands a ticket to the oldest enabled waiter that has none, so a waiter
+/// registered after the call can never take it. The waiter queue only offers
+/// "wake one" / "wake all", not "wake this particular waiter", so a woken
+/// waiter without a ticket passes the wake on while a ticket holder is parked.
+struct NotifyState {
+    /// A `notify_one()` arrived while nobody was waiting
+    permit: bool,
+    /// Sequence number for the next enabled waiter
+    next_seq: u64,
+    /// Enabled waiters without a ticket
+    waiting: BTreeSet<u64>,
+    /// Enabled waiters holding a `notify_one()` ticket they have not claimed
+    notified: BTreeSet<u64>,
+    /// Enabled waiters registered on the waiter queue
+    parked: BTreeSet<u64>,
+    /// Bumped by `notify_waiters()`; waiters enabled before the bump complete
+    generation: u64,
+}
+
+impl NotifyState {
+    /// Give a ticket to the oldest waiter without one, if any
+    fn hand_out_ticket(&mut self) -> bool {
+        match self.waiting.pop_first() {
+            Some(seq) => {
+                self.notified.insert(seq);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a ticket holder may be asleep in the waiter queue
+    fn ticket_holder_parked(&self) -> bool {
+        self.notified.iter().any(|seq| self.parked.contains(seq))
+    }
+}
+
+impl<W: WaiterQueueTrait> NotifyGeneric<W> {
+    /// Create a new `Notify` with no stored permit
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_permits(true)
+    }
+
+    /// Create a `Notify` whose `notify_one()` is lost when nobody is waiting
+    ///
+    /// This is condition-variable behaviour, used by [`Condvar`](crate::Condvar).
+    pub(crate) fn without_permits() -> Self {
+        Self::with_permits(false)
+    }
+
+    fn with_permits(store_permits: bool) -> Self {
+        Self {
+            state: Mutex::new(NotifyState {
+                permit: false,
+                next_seq: 0,
+                waiting: BTreeSet::new(),
+                notified: BTreeSet::new(),
+                parked: BTreeSet::new(),
+                generation: 0,
+            }),
+            store_permits,
+            waiters: W::new(),
+        }
+    }
+
+    /// Wait for a notification
+    ///
+    /// The returned future registers interest when first polled or
+    /// [enabled](Notified::enable).
+    pub fn notified(&self) -> Notified<'_, W> {
+        Notified {
+            notify: self,
+            state: NotifiedState::Init,
+            wait: None,
+        }
+    }
+
+    /// Wake one waiting task, or store a permit for the next one
+    ///
+    /// The oldest waiting task is picked; a future enabled after this call
+    /// cannot complete for it. At most one permit is stored: calling this
+    /// repeatedly with no waiters releases a single future `notified().await`.
+    pub fn notify_one(&self) {
+        let wake = {
+            let mut state = self.state.lock();
+            if state.hand_out_ticket() {
+                true
+            } else {
+                if self.store_permits {
+                    state.permit = true;
+                }
+                false
+            }
+        };
+        if wake {
+            self.waiters.wake_one();
+        }
+    }
+
+    /// Wake every task currently waiting
+    ///
+    /// Only futures that were polled or enabled before this call complete; no
+    /// permit is stored for later callers.
+    pub fn notify_waiters(&self) {
+        {
+            let mut state = self.state.lock();
+            state.generation = state.generation.wrapping_add(1);
+            // Every current waiter completes via the generation, including any
+            // that held a ticket
+            state.waiting.clear();
+            state.notified.clear();
+            state.parked.clear();
+        }
+        self.waiters.wake_all();
+    }
+}
+
+impl<W: WaiterQueueTrait> Default for NotifyGeneric<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Future returned by `Notify::notified()`
+pub struct Notified<'a, W: WaiterQueueTrait> {
+    notify: &'a NotifyGeneric<W>,
+    state: NotifiedState,
+    /// Pending registration on the waiter queue
+    wait: Option<Pin<Box<dyn Future<Output = ()> + 'a>>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NotifiedState {
+    /// Not yet enabled
+    Init,
+    /// Registered as `seq`, enabled during `generation`
+    Waiting { generation: u64, seq: u64 },
+    /// Completed
+    Done,
+}
+
+impl<'a, W: WaiterQueueTrait> Notified<'a, W> {
+    /// Register interest in notifications without waiting
+    ///
+    /// After this, a `notify_waiters()` call completes the future even if it has
+    /// not been polled yet, and `notify_one()` may pick it. Returns `true` if the
+    /// future is already complete (e.g. it consumed a stored permit).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::Notify;
+    /// use std::pin::pin;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// # async fn example(notify: &Notify, ready: &AtomicBool) {
+    /// loop {
+    ///     let mut notified = pin!(notify.notified());
+    ///     notified.as_mut().enable();
+    ///     if ready.load(Ordering::Acquire) {
+    ///         break;
+    ///     }
+    ///     notified.await;
+    /// }
+    /// # }
+    /// ```
+    pub fn enable(mut self: Pin<&mut Self>) -> bool {
+        if self.state == NotifiedState::Init {
+            let mut state = self.notify.state.lock();
+            self.state = if state.permit {
+                state.permit = false;
+                NotifiedState::Done
+            } else {
+                let seq = state.next_seq;
+                state.next_seq += 1;
+                state.waiting.insert(seq);
+                NotifiedState::Waiting {
+                    generation: state.generation,
+                    seq,
+                }
+            };
+        }
+        self.state == NotifiedState::Done
+    }
+
+    /// Try to complete: via `notify_waiters()` or by claiming our ticket
+    fn try_complete(&mut self) -> bool {
+        let NotifiedState::Waiting { generation, seq } = self.state else {
+            return self.state == NotifiedState::Done;
+        };
+        let mut state = self.notify.state.lock();
+        if state.generation != generation {
+            // Waiters were released by notify_waiters()
+        } else if state.notified.remove(&seq) {
+            state.parked.remove(&seq);
+        } else {
+            return false;
+        }
+        self.state = NotifiedState::Done;
+        true
+    }
+}
+
+impl<'a, W: WaiterQueueTrait> Future for Notified<'a, W> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.as_mut().enable() {
+            return Poll::Ready(());
+        }
+
+        loop {
+            if self.try_complete() {
+                self.wait = None;
+                return Poll::Ready(());
+            }
+
+            if self.wait.is_none() {
+                let NotifiedState::Waiting { generation, seq } = self.state else {
+                    unreachable!("enabled future is waiting");
+                };
+                let notify = self.notify;
+                notify.state.lock().parked.insert(seq);
+                // CRITICAL: re-check under registration so a ticket or
+                // notify_waiters() issued after try_complete() is not missed
+                self.wait = Some(Box::pin(notify.waiters.add_waiter_if(move || {
+                    let state = notify.state.lock();
+                    state.generation != generation || state.notified.contains(&seq)
+                })));
+            }
+
+            match self.wait.as_mut().map(|wait| wait.as_mut().poll(cx)) {
+                Some(Poll::Ready(())) => {
+                    self.wait = None;
+                    if self.try_complete() {
+                        return Poll::Ready(());
+                    }
+                    // Woken without a ticket: the wake may have been meant for
+                    // a ticket holder still parked, so pass it on before
+                    // parking again
+                    let forward = {
+                        let mut state = self.notify.state.lock();
+                        if let NotifiedState::Waiting { seq, .. } = self.state {
+                            state.parked.remove(&seq);
+                        }
+                        state.ticket_holder_parked()
+                    };
+                    if forward {
+                        self.notify.waiters.wake_one();
+                    }
+                }
+                _ => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<'a, W: WaiterQueueTrait> Drop for Notified<'a, W> {
+    fn drop(&mut self) {
+        // Deregister from the queue before touching the counters
+        self.wait = None;
+
+        let NotifiedState::Waiting { generation, seq } = self.state else {
+            return;
+        };
+        let forward = {
+            let mut state = self.notify.state.lock();
+            if state.generation != generation {
+                // Completed by notify_waiters(); nothing to give back
+                return;
+            }
+            state.parked.remove(&seq);
+            if !state.waiting.remove(&seq) && state.notified.remove(&seq) {
+                // Our ticket goes to the next oldest waiter, or, with no one
+                // left to claim it, is treated like a notify_one() with no waiters
+                if !state.hand_out_ticket() && self.notify.store_permits {
+                    state.permit = true;
+                }
+            }
+            // We may have swallowed the wake for an outstanding ticket
+            state.ticket_holder_parked()
+        };
+        if forward {
+            self.notify.waiters.wake_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+
+    fn poll_once<F: Future + Unpin>(fut: &mut F) -> Poll<F::Output> {
+        Pin::new(fut).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn test_permit_is_stored_once() {
+        let notify = Notify::new();
+        notify.notify_one();
+        notify.notify_one();
+
+        let mut first = notify.notified();
+        assert!(Pin::new(&mut first).enable());
+
+        // Permits do not accumulate
+        let mut second = notify.notified();
+        assert!(!Pin::new(&mut second).enable());
+    }
+
+    #[test]
+    fn test_notify_waiters_stores_no_permit() {
+        let notify = Notify::new();
+        notify.notify_waiters();
+
+        let mut notified = notify.notified();
+        assert!(!Pin::new(&mut notified).enable());
+    }
+
+    #[test]
+    fn test_enabled_future_receives_notify_waiters() {
+        let notify = Notify::new();
+        let mut notified = notify.notified();
+        Pin::new(&mut notified).enable();
+
+        notify.notify_waiters();
+        assert!(notified.try_complete());
+
+        // Futures created afterwards are unaffected
+        let mut later = notify.notified();
+        assert!(!Pin::new(&mut later).enable());
+    }
+
+    #[test]
+    fn test_notify_one_picks_single_enabled_waiter() {
+        let notify = Notify::new();
+        let mut a = notify.notified();
+        let mut b = notify.notified();
+        Pin::new(&mut a).enable();
+        Pin::new(&mut b).enable();
+
+        notify.notify_one();
+        let completed = [a.try_complete(), b.try_complete()];
+        assert_eq!(completed.iter().filter(|done| **done).count(), 1);
+        assert!(!notify.state.lock().permit);
+    }
+
+    #[test]
+    fn test_dropped_ticket_becomes_permit() {
+        let notify = Notify::new();
+        let mut notified = notify.notified();
+        Pin::new(&mut notified).enable();
+
+        notify.notify_one();
+        drop(notified);
+
+        let mut next = notify.notified();
+        assert!(Pin::new(&mut next).enable());
+    }
+
+    /// A waker that records being woken
+    struct Flag(std::sync::atomic::AtomicBool);
+
+    impl std::task::Wake for Flag {
+        fn wake(self: std::sync::Arc<Self>) {
+            self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    /// The FIFO generic queue wakes whoever parked first, which need not be
+    /// the ticket holder; the wake has to be passed on to it
+    #[test]
+    fn test_wake_reaches_ticket_holder_parked_behind() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let notify = NotifyGeneric::<crate::waiter_queue::generic::WaiterQueue>::new();
+        let holder_flag = Arc::new(Flag(AtomicBool::new(false)));
+        let holder_waker = Waker::from(holder_flag.clone());
+        let mut holder_cx = Context::from_waker(&holder_waker);
+        let mut noop = Context::from_waker(Waker::noop());
+
+        // Enabled first but parked last, so the queue wakes `other` first
+        let mut holder = Box::pin(notify.notified());
+        holder.as_mut().enable();
+        let mut other = Box::pin(notify.notified());
+        assert!(other.as_mut().poll(&mut noop).is_pending());
+        assert!(holder.as_mut().poll(&mut holder_cx).is_pending());
+
+        notify.notify_one();
+        assert!(!holder_flag.0.load(Ordering::SeqCst));
+        assert!(other.as_mut().poll(&mut noop).is_pending());
+
+        assert!(holder_flag.0.load(Ordering::SeqCst), "holder never woken");
+        assert!(holder.as_mut().poll(&mut holder_cx).is_ready());
+    }
+
+    #[compio::test]
+    async fn test_poll_pending_then_ready() {
+        let notify = Notify::new();
+        let mut notified = notify.notified();
+        assert!(poll_once(&mut notified).is_pending());
+
+        notify.notify_one();
+        notified.await;
+    }
+}
@@ -0,0 +1,403 @@
+//! Async one-time initialisation
+//!
+//! This module provides `OnceCell<T>`, a cell written at most once whose
+//! initialiser may `.await`, and `Lazy<T, F>`, a value computed by such an
+//! initialiser on first use.
+//!
+//! When several tasks race to initialise a cell, one runs its initialiser and
+//! the rest wait on the crate's [`WaiterQueue`](crate::WaiterQueue) instead of
+//! spinning. If the running initialiser fails or its future is dropped, the
+//! cell goes back to empty and a waiting task runs its own initialiser.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::OnceCell;
+//! use std::sync::Arc;
+//!
+//! async fn load_config() -> String {
+//!     // e.g. read a file with compio::fs
+//!     String::from("threads = 4")
+//! }
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let config = Arc::new(OnceCell::new());
+//!
+//!     let mut handles = Vec::new();
+//!     for _ in 0..10 {
+//!         let config = config.clone();
+//!         handles.push(compio::runtime::spawn(async move {
+//!             // Loaded once; everyone else waits for that load
+//!             config.get_or_init(load_config).await.len()
+//!         }));
+//!     }
+//!     for handle in handles {
+//!         handle.await.unwrap();
+//!     }
+//! }
+//! ```
+
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use std::cell::UnsafeCell;
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// No value and no initialiser running
+const EMPTY: u8 = 0;
+/// An initialiser is running
+const RUNNING: u8 = 1;
+/// The value is written and will not change
+const READY: u8 = 2;
+
+/// A cell that is written at most once, possibly asynchronously
+///
+/// Generic over the waiter queue implementation; use the [`OnceCell`] alias.
+pub struct OnceCellGeneric<T, W: WaiterQueueTrait> {
+    state: AtomicU8,
+    /// Initialised iff `state` is `READY`
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// Tasks waiting for a running initialiser
+    waiters: W,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type OnceCell<T> = OnceCellGeneric<T, WaiterQueue>;
+
+// SAFETY: the value is written once by the task that moved the cell into
+// `RUNNING` and only read after `READY`; moving the cell moves `T`
+unsafe impl<T: Send, W: WaiterQueueTrait + Send> Send for OnceCellGeneric<T, W> {}
+// SAFETY: sharing the cell hands out `&T` to many threads (`T: Sync`) and lets
+// any of them produce the value (`T: Send`)
+unsafe impl<T: Send + Sync, W: WaiterQueueTrait> Sync for OnceCellGeneric<T, W> {}
+
+impl<T, W: WaiterQueueTrait> OnceCellGeneric<T, W> {
+    /// Create an empty cell
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::OnceCell;
+    ///
+    /// let cell = OnceCell::<u32>::new();
+    /// assert!(cell.get().is_none());
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: AtomicU8::new(EMPTY),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            waiters: W::new(),
+        }
+    }
+
+    /// Get the value if the cell is initialised
+    pub fn get(&self) -> Option<&T> {
+        if self.initialized() {
+            // SAFETY: READY is only stored after the value was written, and the
+            // value is never written again
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Get a mutable reference to the value if the cell is initialised
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if self.initialized() {
+            // SAFETY: READY means the value is written; `&mut self` is exclusive
+            Some(unsafe { self.value.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /// Check whether the cell holds a value
+    pub fn initialized(&self) -> bool {
+        self.state.load(Ordering::Acquire) == READY
+    }
+
+    /// Set the value if the cell is empty
+    ///
+    /// # Errors
+    ///
+    /// Returns `value` back if the cell is already initialised or an
+    /// initialiser is running.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.try_start() {
+            self.complete(value);
+            Ok(())
+        } else {
+            Err(value)
+        }
+    }
+
+    /// Get the value, initialising it with `init` if the cell is empty
+    ///
+    /// If another task is initialising the cell, wait for it. If that task's
+    /// initialiser is cancelled, this task runs `init` instead.
+    pub async fn get_or_init<F, Fut>(&self, init: F) -> &T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let result = self
+            .get_or_try_init(|| async { Ok::<T, Infallible>(init().await) })
+            .await;
+        match result {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Get the value, initialising it with the fallible `init` if the cell is
+    /// empty
+    ///
+    /// If another task is initialising the cell, wait for it. If that
+    /// initialiser fails or is cancelled, one waiting task retries with its
+    /// own initialiser.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `init` if this task ran it and it failed; the
+    /// cell stays empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::OnceCell;
+    ///
+    /// # async fn example() -> std::io::Result<()> {
+    /// let cell = OnceCell::new();
+    /// let value = cell
+    ///     .get_or_try_init(|| async { "42".parse::<u32>().map_err(std::io::Error::other) })
+    ///     .await?;
+    /// assert_eq!(*value, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_or_try_init<E, F, Fut>(&self, init: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        loop {
+            if let Some(value) = self.get() {
+                return Ok(value);
+            }
+
+            if self.try_start() {
+                // Resets the cell if `init` fails or this future is dropped
+                let running = Running { cell: self };
+                let value = init().await?;
+                std::mem::forget(running);
+                self.complete(value);
+                return Ok(self.get().expect("cell was just initialised"));
+            }
+
+            // CRITICAL: check the state during registration so a completion
+            // or reset between try_start() and registering is not missed
+            let state = &self.state;
+            self.waiters
+                .add_waiter_if(|| state.load(Ordering::Acquire) != RUNNING)
+                .await;
+        }
+    }
+
+    /// Consume the cell, returning the value if it was initialised
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take()
+    }
+
+    /// Take the value out, leaving the cell empty
+    pub fn take(&mut self) -> Option<T> {
+        if self.initialized() {
+            *self.state.get_mut() = EMPTY;
+            // SAFETY: the value was written and the state no longer says so,
+            // so it is read out exactly once
+            Some(unsafe { self.value.get_mut().assume_init_read() })
+        } else {
+            None
+        }
+    }
+
+    /// Claim the right to write the value
+    fn try_start(&self) -> bool {
+        self.state
+            .compare_exchange(EMPTY, RUNNING, Ordering::Acquire, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Write the value after a successful `try_start()` and release waiters
+    fn complete(&self, value: T) {
+        // SAFETY: `try_start()` made this task the only writer, and readers
+        // wait for READY
+        unsafe { (*self.value.get()).write(value) };
+        self.state.store(READY, Ordering::Release);
+        self.waiters.wake_all();
+    }
+}
+
+impl<T, W: WaiterQueueTrait> Default for OnceCellGeneric<T, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, W: WaiterQueueTrait> From<T> for OnceCellGeneric<T, W> {
+    /// Create an initialised cell
+    fn from(value: T) -> Self {
+        let cell = Self::new();
+        let _ = cell.set(value);
+        cell
+    }
+}
+
+impl<T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for OnceCellGeneric<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_tuple("OnceCell");
+        match self.get() {
+            Some(value) => d.field(value),
+            None => d.field(&format_args!("<uninit>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T, W: WaiterQueueTrait> Drop for OnceCellGeneric<T, W> {
+    fn drop(&mut self) {
+        drop(self.take());
+    }
+}
+
+/// Guard held while an initialiser runs
+///
+/// Dropped without being forgotten when the initialiser fails or its future is
+/// cancelled: the cell goes back to empty and the waiters are woken so one of
+/// them can retry. All are woken because any of them may have been dropped
+/// too.
+struct Running<'a, T, W: WaiterQueueTrait> {
+    cell: &'a OnceCellGeneric<T, W>,
+}
+
+impl<'a, T, W: WaiterQueueTrait> Drop for Running<'a, T, W> {
+    fn drop(&mut self) {
+        self.cell.state.store(EMPTY, Ordering::Release);
+        self.cell.waiters.wake_all();
+    }
+}
+
+/// A value initialised asynchronously on first use
+///
+/// Generic over the waiter queue implementation; use the [`Lazy`] alias.
+///
+/// The initialiser is `Fn` rather than `FnOnce` so it can be run again if a
+/// previous attempt was cancelled.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use compio_sync::Lazy;
+///
+/// # async fn example() {
+/// let table = Lazy::new(|| async { vec![1, 2, 3] });
+/// assert_eq!(table.force().await.len(), 3);
+/// # }
+/// ```
+pub struct LazyGeneric<T, F, W: WaiterQueueTrait> {
+    cell: OnceCellGeneric<T, W>,
+    init: F,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type Lazy<T, F> = LazyGeneric<T, F, WaiterQueue>;
+
+impl<T, F, Fut, W> LazyGeneric<T, F, W>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = T>,
+    W: WaiterQueueTrait,
+{
+    /// Create a lazy value computed by `init` on first use
+    pub fn new(init: F) -> Self {
+        Self {
+            cell: OnceCellGeneric::new(),
+            init,
+        }
+    }
+
+    /// Get the value, running the initialiser if needed
+    pub async fn force(&self) -> &T {
+        self.cell.get_or_init(&self.init).await
+    }
+
+    /// Get the value if it has been initialised
+    pub fn get(&self) -> Option<&T> {
+        self.cell.get()
+    }
+}
+
+impl<T: fmt::Debug, F, W: WaiterQueueTrait> fmt::Debug for LazyGeneric<T, F, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy").field("cell", &self.cell).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_set_once() {
+        let cell = OnceCell::new();
+        assert_eq!(cell.set(1), Ok(()));
+        assert_eq!(cell.set(2), Err(2));
+        assert_eq!(cell.get(), Some(&1));
+        assert_eq!(format!("{:?}", cell), "OnceCell(1)");
+    }
+
+    #[test]
+    fn test_value_dropped_with_cell() {
+        let value = Arc::new(());
+        let cell = OnceCell::from(value.clone());
+        assert_eq!(Arc::strong_count(&value), 2);
+        drop(cell);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_take_empties_cell() {
+        let mut cell = OnceCell::from(String::from("a"));
+        assert_eq!(cell.take().as_deref(), Some("a"));
+        assert!(cell.get().is_none());
+        assert_eq!(cell.set(String::from("b")), Ok(()));
+        assert_eq!(cell.into_inner().as_deref(), Some("b"));
+    }
+
+    #[compio::test]
+    async fn test_failed_init_leaves_cell_empty() {
+        let cell = OnceCell::<u32>::new();
+        let result = cell.get_or_try_init(|| async { Err("nope") }).await;
+        assert_eq!(result, Err("nope"));
+        assert_eq!(cell.state.load(Ordering::Acquire), EMPTY);
+
+        assert_eq!(*cell.get_or_init(|| async { 7 }).await, 7);
+    }
+
+    #[compio::test]
+    async fn test_lazy_runs_once() {
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let lazy = Lazy::new(|| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            5
+        });
+        assert!(lazy.get().is_none());
+        assert_eq!(*lazy.force().await, 5);
+        assert_eq!(*lazy.force().await, 5);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
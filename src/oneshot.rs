@@ -0,0 +1,342 @@
+//! Single-value channel between two tasks
+//!
+//! [`channel()`] returns a [`Sender`] that can send one value and a
+//! [`Receiver`] that is a future resolving to it. Either side notices when the
+//! other goes away:
+//!
+//! - Awaiting the `Receiver` fails with [`RecvError`] if the `Sender` is dropped
+//!   without sending; [`Receiver::is_closed`] reports this without waiting.
+//! - [`Sender::send`] hands the value back if the `Receiver` is dropped or
+//!   [closed](Receiver::close); [`Sender::closed`] waits for that to happen,
+//!   e.g. to abandon work nobody wants any more.
+//!
+//! Each side has exactly one task to wake, so wakeups go through an
+//! `AtomicWaker` per side, the same lock-free slot the generic waiter queue
+//! uses for its single-waiter fast path.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::oneshot;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let (tx, rx) = oneshot::channel();
+//!
+//!     compio::runtime::spawn(async move {
+//!         let checksum = 0xdead_beef_u32;
+//!         let _ = tx.send(checksum);
+//!     })
+//!     .detach();
+//!
+//!     match rx.await {
+//!         Ok(checksum) => println!("checksum {:x}", checksum),
+//!         Err(_) => println!("task gave up"),
+//!     }
+//! }
+//! ```
+
+use atomic_waker::AtomicWaker;
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// The value has been written and may be taken by the receiver
+const VALUE_SENT: u8 = 1 << 0;
+/// The sender was dropped without sending
+const TX_DROPPED: u8 = 1 << 1;
+/// The receiver was dropped or closed; the sender can no longer send
+const RX_CLOSED: u8 = 1 << 2;
+
+/// Create a oneshot channel, returning the sending and receiving halves
+///
+/// # Example
+///
+/// ```rust
+/// use compio_sync::oneshot;
+///
+/// let (tx, mut rx) = oneshot::channel();
+/// tx.send(5).unwrap();
+/// assert_eq!(rx.try_recv(), Ok(5));
+/// ```
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner {
+        state: AtomicU8::new(0),
+        value: UnsafeCell::new(None),
+        rx_waker: AtomicWaker::new(),
+        tx_waker: AtomicWaker::new(),
+    });
+    (
+        Sender {
+            inner: Some(inner.clone()),
+        },
+        Receiver { inner },
+    )
+}
+
+/// State shared by both halves
+struct Inner<T> {
+    state: AtomicU8,
+    /// Written by the sender before it sets `VALUE_SENT`, taken by the receiver
+    /// only after it sees `VALUE_SENT`
+    value: UnsafeCell<Option<T>>,
+    /// Receiver waiting for a value
+    rx_waker: AtomicWaker,
+    /// Sender waiting in `closed()`
+    tx_waker: AtomicWaker,
+}
+
+// SAFETY: `value` is handed from the sender to the receiver through `state`, so
+// at most one side touches it at a time
+unsafe impl<T: Send> Send for Inner<T> {}
+// SAFETY: as above; `T` is never shared, only moved between tasks
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+/// Sending half of a [oneshot channel](channel)
+pub struct Sender<T> {
+    /// `None` once the value was sent
+    inner: Option<Arc<Inner<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send `value` to the receiver, consuming the sender
+    ///
+    /// This never waits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `value` back if the receiver was dropped or closed.
+    pub fn send(mut self, value: T) -> Result<(), T> {
+        let inner = self.inner.take().expect("sender used after send");
+
+        // SAFETY: the receiver does not touch `value` until VALUE_SENT is set
+        unsafe { *inner.value.get() = Some(value) };
+        let sent = inner
+            .state
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |state| {
+                (state & RX_CLOSED == 0).then_some(state | VALUE_SENT)
+            })
+            .is_ok();
+
+        if sent {
+            inner.rx_waker.wake();
+            Ok(())
+        } else {
+            // SAFETY: VALUE_SENT was never set, so the receiver never reads it
+            let value = unsafe { (*inner.value.get()).take() }.expect("value was just written");
+            // The sender is gone without having sent: a receiver still waiting
+            // after `close()` must see that, just as if it had been dropped
+            inner.state.fetch_or(TX_DROPPED, Ordering::AcqRel);
+            inner.rx_waker.wake();
+            Err(value)
+        }
+    }
+
+    /// Check whether the receiver was dropped or closed
+    pub fn is_closed(&self) -> bool {
+        self.inner
+            .as_ref()
+            .is_some_and(|inner| inner.state.load(Ordering::Acquire) & RX_CLOSED != 0)
+    }
+
+    /// Wait until the receiver is dropped or closed
+    ///
+    /// Useful to stop producing a value nobody will read.
+    pub async fn closed(&mut self) {
+        let Some(inner) = self.inner.as_ref() else {
+            return;
+        };
+        poll_fn(|cx| {
+            if inner.state.load(Ordering::Acquire) & RX_CLOSED != 0 {
+                return Poll::Ready(());
+            }
+            inner.tx_waker.register(cx.waker());
+            // CRITICAL: re-check after registering so a close between the
+            // check above and registering is not missed
+            if inner.state.load(Ordering::Acquire) & RX_CLOSED != 0 {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.take() {
+            inner.state.fetch_or(TX_DROPPED, Ordering::AcqRel);
+            inner.rx_waker.wake();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// Receiving half of a [oneshot channel](channel)
+///
+/// Await it to get the value.
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Take the value if it has been sent, without waiting
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if nothing has been sent yet
+    /// - [`TryRecvError::Closed`] if the sender was dropped without sending (a
+    ///   send refused after [`close`](Self::close) counts), or the value was
+    ///   already received
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let state = self.inner.state.load(Ordering::Acquire);
+        if state & VALUE_SENT != 0 {
+            // SAFETY: the sender wrote the value before setting VALUE_SENT and
+            // never touches it again
+            unsafe { (*self.inner.value.get()).take() }.ok_or(TryRecvError::Closed)
+        } else if state & TX_DROPPED != 0 {
+            Err(TryRecvError::Closed)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Check whether the sender was dropped without sending a value
+    ///
+    /// Once this returns `true`, awaiting the receiver fails immediately.
+    pub fn is_closed(&self) -> bool {
+        let state = self.inner.state.load(Ordering::Acquire);
+        state & (VALUE_SENT | TX_DROPPED) == TX_DROPPED
+    }
+
+    /// Refuse any value not sent yet
+    ///
+    /// Later `send()` calls hand their value back and [`Sender::closed`]
+    /// completes. A value sent before this call can still be received.
+    pub fn close(&mut self) {
+        self.inner.state.fetch_or(RX_CLOSED, Ordering::AcqRel);
+        self.inner.tx_waker.wake();
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.try_recv() {
+            Ok(value) => return Poll::Ready(Ok(value)),
+            Err(TryRecvError::Closed) => return Poll::Ready(Err(RecvError(()))),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        self.inner.rx_waker.register(cx.waker());
+        // CRITICAL: re-check after registering so a send between the check
+        // above and registering is not missed
+        match self.try_recv() {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError(()))),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// Error returned by awaiting a [`Receiver`] when the sender was dropped
+/// without sending
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError(());
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Error returned by [`Receiver::try_recv`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No value has been sent yet
+    Empty,
+    /// The sender was dropped without sending, or the value was already taken
+    Closed,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("channel empty"),
+            TryRecvError::Closed => f.write_str("channel closed"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send_after_close_returns_value() {
+        let (tx, mut rx) = channel();
+        rx.close();
+        assert!(tx.is_closed());
+        assert_eq!(tx.send(String::from("x")), Err(String::from("x")));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+        assert!(rx.is_closed());
+    }
+
+    #[test]
+    fn test_value_sent_before_close_is_kept() {
+        let (tx, mut rx) = channel();
+        tx.send(1).unwrap();
+        rx.close();
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    }
+
+    #[test]
+    fn test_unreceived_value_dropped_with_channel() {
+        let value = Arc::new(());
+        let (tx, rx) = channel();
+        tx.send(value.clone()).unwrap();
+        drop(rx);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_sender_drop_closes_receiver() {
+        let (tx, mut rx) = channel::<u8>();
+        assert!(!rx.is_closed());
+        drop(tx);
+        assert!(rx.is_closed());
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    }
+}
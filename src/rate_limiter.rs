@@ -0,0 +1,383 @@
+//! Async token-bucket rate limiter for compio
+//!
+//! A [`Semaphore`](crate::Semaphore) bounds how many operations run at once;
+//! `RateLimiter` bounds how many run per unit of time. Tokens refill
+//! continuously at a fixed rate up to a burst size, and `acquire(n)` waits
+//! until `n` tokens are available. Use token counts of 1 to cap IOPS, or byte
+//! counts to cap bandwidth.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::RateLimiter;
+//!
+//! # async fn example() {
+//! // At most 1000 operations per second, with bursts of up to 50
+//! let iops = RateLimiter::new(1000.0, 50);
+//!
+//! for _ in 0..10_000 {
+//!     iops.acquire(1).await;
+//!     // issue one I/O operation
+//! }
+//! # }
+//! ```
+//!
+//! # Waiting
+//!
+//! Only one waiter at a time (the oldest) sleeps on a compio timer until enough
+//! tokens have refilled for its request; everyone else parks on the
+//! [`WaiterQueue`](crate::WaiterQueue). When the timer holder finishes, the next
+//! waiter is woken to take over. Requests that arrive while someone is waiting
+//! queue behind them rather than taking freshly refilled tokens.
+
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use parking_lot::Mutex;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// An async token-bucket rate limiter
+///
+/// Generic over the waiter queue implementation; use the [`RateLimiter`] alias.
+pub struct RateLimiterGeneric<W: WaiterQueueTrait> {
+    /// Tokens added per second
+    rate: f64,
+    /// Maximum number of tokens the bucket holds
+    burst: usize,
+    /// Bucket state (short critical sections, never held across await)
+    bucket: Mutex<Bucket>,
+    /// Queue of waiters that are not holding the refill timer
+    waiters: W,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type RateLimiter = RateLimiterGeneric<WaiterQueue>;
+
+/// Mutable token-bucket state
+struct Bucket {
+    /// Tokens currently available (fractional so slow rates accumulate)
+    tokens: f64,
+    /// When `tokens` was last brought up to date
+    last_refill: Instant,
+    /// Whether a waiter is sleeping on the refill timer
+    timer_active: bool,
+    /// Set when the timer was handed over with `wake_one()` and cleared by the
+    /// waiter that picks it up, so a cancelled wakee can pass the wake on
+    handoff: bool,
+}
+
+impl Bucket {
+    fn refill(&mut self, rate: f64, burst: usize) {
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * rate).min(burst as f64);
+        self.last_refill = now;
+    }
+
+    /// Time until `n` tokens are available at `rate`
+    ///
+    /// Saturates at `Duration::MAX` when a tiny rate makes the wait too long to
+    /// represent.
+    fn wait_for(&self, n: usize, rate: f64) -> Duration {
+        Duration::try_from_secs_f64(((n as f64 - self.tokens) / rate).max(0.0))
+            .unwrap_or(Duration::MAX)
+    }
+}
+
+impl<W: WaiterQueueTrait> RateLimiterGeneric<W> {
+    /// Create a rate limiter refilling `rate` tokens per second, holding at most
+    /// `burst` tokens
+    ///
+    /// The bucket starts full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not a positive finite number or `burst` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::RateLimiter;
+    ///
+    /// // 64 MiB/s with up to 4 MiB in a single burst
+    /// let bandwidth = RateLimiter::new(64.0 * 1024.0 * 1024.0, 4 * 1024 * 1024);
+    /// assert_eq!(bandwidth.burst(), 4 * 1024 * 1024);
+    /// ```
+    #[must_use]
+    pub fn new(rate: f64, burst: usize) -> Self {
+        assert!(
+            rate.is_finite() && rate > 0.0,
+            "RateLimiter rate must be positive and finite"
+        );
+        assert!(burst > 0, "RateLimiter burst must be at least 1");
+        Self {
+            rate,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst as f64,
+                last_refill: Instant::now(),
+                timer_active: false,
+                handoff: false,
+            }),
+            waiters: W::new(),
+        }
+    }
+
+    /// Acquire `n` tokens, waiting asynchronously until they have refilled
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds [`burst()`](Self::burst), since such a request could
+    /// never be satisfied.
+    ///
+    /// # Cancel safety
+    ///
+    /// Dropping the future before it completes consumes no tokens and passes
+    /// its turn on to the next waiter.
+    pub async fn acquire(&self, n: usize) {
+        self.check_request(n);
+
+        // Present while this task owns the refill timer
+        let mut timer: Option<TimerGuard<'_, W>> = None;
+
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock();
+                bucket.refill(self.rate, self.burst);
+
+                if timer.is_some() || !bucket.timer_active {
+                    if bucket.tokens >= n as f64 {
+                        bucket.tokens -= n as f64;
+                        drop(bucket);
+                        // Releasing the timer hands over to the next waiter
+                        drop(timer);
+                        return;
+                    }
+                    if timer.is_none() {
+                        bucket.timer_active = true;
+                        timer = Some(TimerGuard { limiter: self });
+                    }
+                    Some(bucket.wait_for(n, self.rate))
+                } else {
+                    None
+                }
+            };
+
+            match wait {
+                // A wait past the end of time never finishes
+                Some(duration) => match Instant::now().checked_add(duration) {
+                    Some(deadline) => compio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                },
+                None => {
+                    // Park until the timer holder finishes
+                    // CRITICAL: re-check under registration so a release between
+                    // the check above and registering is not missed
+                    let queued = QueuedGuard { limiter: self };
+                    self.waiters
+                        .add_waiter_if(|| !self.bucket.lock().timer_active)
+                        .await;
+                    queued.resumed();
+                }
+            }
+        }
+    }
+
+    /// Try to acquire `n` tokens without waiting
+    ///
+    /// Fails while other tasks are waiting, so that callers polling with
+    /// `try_acquire` cannot starve them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimited`] with an estimate of how long until `n` tokens
+    /// will be available.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds [`burst()`](Self::burst).
+    pub fn try_acquire(&self, n: usize) -> Result<(), RateLimited> {
+        self.check_request(n);
+
+        let mut bucket = self.bucket.lock();
+        bucket.refill(self.rate, self.burst);
+        if !bucket.timer_active && bucket.tokens >= n as f64 {
+            bucket.tokens -= n as f64;
+            Ok(())
+        } else {
+            Err(RateLimited {
+                retry_after: bucket.wait_for(n, self.rate),
+            })
+        }
+    }
+
+    /// Get the number of whole tokens currently available
+    #[must_use]
+    pub fn available_tokens(&self) -> usize {
+        let mut bucket = self.bucket.lock();
+        bucket.refill(self.rate, self.burst);
+        bucket.tokens as usize
+    }
+
+    /// Get the refill rate in tokens per second
+    #[must_use]
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Get the maximum number of tokens the bucket holds
+    #[must_use]
+    pub fn burst(&self) -> usize {
+        self.burst
+    }
+
+    fn check_request(&self, n: usize) {
+        assert!(
+            n <= self.burst,
+            "RateLimiter request of {} tokens exceeds burst of {}",
+            n,
+            self.burst
+        );
+    }
+}
+
+/// Ownership of the refill timer; released (and handed over) on drop
+struct TimerGuard<'a, W: WaiterQueueTrait> {
+    limiter: &'a RateLimiterGeneric<W>,
+}
+
+impl<'a, W: WaiterQueueTrait> Drop for TimerGuard<'a, W> {
+    fn drop(&mut self) {
+        {
+            let mut bucket = self.limiter.bucket.lock();
+            bucket.timer_active = false;
+            bucket.handoff = true;
+        }
+        self.limiter.waiters.wake_one();
+    }
+}
+
+/// A waiter parked on the queue
+///
+/// If the future is dropped after being handed the timer but before running,
+/// the wake is forwarded so the remaining waiters are not stranded.
+struct QueuedGuard<'a, W: WaiterQueueTrait> {
+    limiter: &'a RateLimiterGeneric<W>,
+}
+
+impl<'a, W: WaiterQueueTrait> QueuedGuard<'a, W> {
+    /// The waiter ran after being woken: it has picked up the handoff
+    fn resumed(self) {
+        self.limiter.bucket.lock().handoff = false;
+        std::mem::forget(self);
+    }
+}
+
+impl<'a, W: WaiterQueueTrait> Drop for QueuedGuard<'a, W> {
+    fn drop(&mut self) {
+        let forward = {
+            let bucket = self.limiter.bucket.lock();
+            bucket.handoff && !bucket.timer_active
+        };
+        if forward {
+            self.limiter.waiters.wake_one();
+        }
+    }
+}
+
+/// Error returned by `RateLimiter::try_acquire()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    /// Estimated time until the requested tokens are available
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rate limited, retry after {:?}", self.retry_after)
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+
+    #[test]
+    fn test_try_acquire_burst_then_limited() {
+        let limiter = RateLimiter::new(10.0, 5);
+        assert_eq!(limiter.available_tokens(), 5);
+
+        assert!(limiter.try_acquire(3).is_ok());
+        assert!(limiter.try_acquire(2).is_ok());
+
+        let err = limiter.try_acquire(1).unwrap_err();
+        assert!(err.retry_after > Duration::ZERO);
+        assert!(err.retry_after <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_refill_caps_at_burst() {
+        let limiter = RateLimiter::new(1000.0, 2);
+        assert!(limiter.try_acquire(2).is_ok());
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(limiter.available_tokens(), 2);
+    }
+
+    #[compio::test]
+    async fn test_try_acquire_yields_to_waiter() {
+        let limiter = RateLimiter::new(1000.0, 1);
+        assert!(limiter.try_acquire(1).is_ok());
+
+        // Register a waiter: it takes the refill timer
+        let mut fut = Box::pin(limiter.acquire(1));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        // Even once tokens refill, try_acquire must not jump the queue
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(limiter.try_acquire(1).is_err());
+
+        // Cancelling the waiter releases the timer
+        drop(fut);
+        assert!(limiter.try_acquire(1).is_ok());
+    }
+
+    #[compio::test]
+    async fn test_ready_acquire_completes_immediately() {
+        let limiter = RateLimiter::new(1.0, 4);
+        let mut fut = Box::pin(limiter.acquire(4));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(()));
+        assert_eq!(limiter.available_tokens(), 0);
+    }
+
+    #[test]
+    fn test_tiny_rate_saturates_retry_after() {
+        let limiter = RateLimiter::new(1e-20, 1);
+        assert!(limiter.try_acquire(1).is_ok());
+
+        let err = limiter.try_acquire(1).unwrap_err();
+        assert_eq!(err.retry_after, Duration::MAX);
+    }
+
+    #[compio::test]
+    async fn test_tiny_rate_acquire_waits() {
+        let limiter = RateLimiter::new(1e-20, 1);
+        assert!(limiter.try_acquire(1).is_ok());
+
+        let mut fut = Box::pin(limiter.acquire(1));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds burst")]
+    fn test_request_over_burst_panics() {
+        let limiter = RateLimiter::new(10.0, 5);
+        let _ = limiter.try_acquire(6);
+    }
+}
@@ -0,0 +1,569 @@
+//! Asynchronous reader-writer lock for compio
+//!
+//! This module provides `RwLock<T>`, which allows any number of concurrent
+//! readers or a single writer. Waiting tasks park on the crate's
+//! [`WaiterQueue`](crate::WaiterQueue), so guards can be held across `.await`.
+//!
+//! # Writer preference
+//!
+//! Once a writer is waiting, new readers queue behind it instead of joining the
+//! readers already holding the lock. A steady stream of readers therefore
+//! cannot starve writers: the writer gets the lock as soon as the current
+//! readers finish. Waiting readers are admitted when no writer holds or is
+//! waiting for the lock.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use compio_sync::RwLock;
+//! use std::collections::HashMap;
+//!
+//! # async fn example() {
+//! let cache = RwLock::new(HashMap::new());
+//!
+//! cache.write().await.insert("/etc/hosts", 158u64);
+//!
+//! let size = cache.read().await.get("/etc/hosts").copied();
+//! assert_eq!(size, Some(158));
+//! # }
+//! ```
+
+use crate::mutex::TryLockError;
+use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// `state` bit set while a writer holds the lock
+const WRITER: usize = 1 << (usize::BITS - 1);
+
+/// Maximum number of concurrent readers (the remaining `state` bits)
+const MAX_READERS: usize = WRITER - 1;
+
+/// An async reader-writer lock protecting a value of type `T`
+///
+/// Generic over the waiter queue implementation; use the [`RwLock`] alias.
+pub struct RwLockGeneric<T, W: WaiterQueueTrait> {
+    /// Reader count, or `WRITER` while write-locked
+    state: AtomicUsize,
+    /// Number of writers waiting in `write()`; non-zero blocks new readers
+    waiting_writers: AtomicUsize,
+    /// Readers waiting for the writer (and waiting writers) to finish
+    readers: W,
+    /// Writers waiting for the lock to become free
+    writers: W,
+    data: UnsafeCell<T>,
+}
+
+/// Public type alias using platform-specific WaiterQueue
+pub type RwLock<T> = RwLockGeneric<T, WaiterQueue>;
+
+// SAFETY: access to `data` is serialised by `state`; moving the lock moves `T`
+unsafe impl<T: Send, W: WaiterQueueTrait + Send> Send for RwLockGeneric<T, W> {}
+// SAFETY: readers on several threads share `&T` (needs `T: Sync`) and a writer
+// on any thread gets `&mut T` (needs `T: Send`)
+unsafe impl<T: Send + Sync, W: WaiterQueueTrait> Sync for RwLockGeneric<T, W> {}
+
+impl<T, W: WaiterQueueTrait> RwLockGeneric<T, W> {
+    /// Create a new unlocked `RwLock` holding `value`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::RwLock;
+    ///
+    /// let lock = RwLock::new(5);
+    /// assert_eq!(*lock.try_read().unwrap(), 5);
+    /// ```
+    pub fn new(value: T) -> Self {
+        Self {
+            state: AtomicUsize::new(0),
+            waiting_writers: AtomicUsize::new(0),
+            readers: W::new(),
+            writers: W::new(),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// Lock for shared read access, waiting while a writer holds or is waiting
+    /// for the lock
+    pub async fn read(&self) -> RwLockReadGuard<'_, T, W> {
+        self.acquire_read().await;
+        RwLockReadGuard { lock: self }
+    }
+
+    /// Lock for exclusive write access, waiting for all other guards to drop
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T, W> {
+        self.acquire_write().await;
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Try to lock for shared read access without waiting
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if a writer holds or is waiting for the lock.
+    pub fn try_read(&self) -> Result<RwLockReadGuard<'_, T, W>, TryLockError> {
+        if self.try_acquire_read() {
+            Ok(RwLockReadGuard { lock: self })
+        } else {
+            Err(TryLockError::new())
+        }
+    }
+
+    /// Try to lock for exclusive write access without waiting
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if any guard is held.
+    pub fn try_write(&self) -> Result<RwLockWriteGuard<'_, T, W>, TryLockError> {
+        if self.try_acquire_write() {
+            Ok(RwLockWriteGuard { lock: self })
+        } else {
+            Err(TryLockError::new())
+        }
+    }
+
+    /// Lock for reading through an `Arc`, returning a `'static` guard
+    pub async fn read_owned(self: Arc<Self>) -> OwnedRwLockReadGuard<T, W> {
+        self.acquire_read().await;
+        OwnedRwLockReadGuard { lock: self }
+    }
+
+    /// Lock for writing through an `Arc`, returning a `'static` guard
+    pub async fn write_owned(self: Arc<Self>) -> OwnedRwLockWriteGuard<T, W> {
+        self.acquire_write().await;
+        OwnedRwLockWriteGuard { lock: self }
+    }
+
+    /// Try to lock for reading through an `Arc` without waiting
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if a writer holds or is waiting for the lock.
+    pub fn try_read_owned(self: Arc<Self>) -> Result<OwnedRwLockReadGuard<T, W>, TryLockError> {
+        if self.try_acquire_read() {
+            Ok(OwnedRwLockReadGuard { lock: self })
+        } else {
+            Err(TryLockError::new())
+        }
+    }
+
+    /// Try to lock for writing through an `Arc` without waiting
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if any guard is held.
+    pub fn try_write_owned(self: Arc<Self>) -> Result<OwnedRwLockWriteGuard<T, W>, TryLockError> {
+        if self.try_acquire_write() {
+            Ok(OwnedRwLockWriteGuard { lock: self })
+        } else {
+            Err(TryLockError::new())
+        }
+    }
+
+    /// Get a mutable reference to the value without locking
+    ///
+    /// The `&mut self` borrow guarantees no guards exist.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Consume the lock, returning the value
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    fn try_acquire_read(&self) -> bool {
+        let mut current = self.state.load(Ordering::Acquire);
+        loop {
+            if current & WRITER != 0 || self.waiting_writers.load(Ordering::SeqCst) != 0 {
+                return false;
+            }
+            assert!(current < MAX_READERS, "RwLock reader count overflow");
+            match self.state.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    fn try_acquire_write(&self) -> bool {
+        self.state
+            .compare_exchange(0, WRITER, Ordering::SeqCst, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    async fn acquire_read(&self) {
+        while !self.try_acquire_read() {
+            // Readers are only ever released with wake_all(), so a cancelled
+            // reader cannot swallow a wake meant for another task
+            let (state, waiting_writers) = (&self.state, &self.waiting_writers);
+            self.readers
+                .add_waiter_if(|| {
+                    state.load(Ordering::SeqCst) & WRITER == 0
+                        && waiting_writers.load(Ordering::SeqCst) == 0
+                })
+                .await;
+        }
+    }
+
+    async fn acquire_write(&self) {
+        if self.try_acquire_write() {
+            return;
+        }
+
+        // Announce ourselves first so that new readers stop entering
+        let _waiting = WaitingWriter::new(self);
+        loop {
+            if self.try_acquire_write() {
+                return;
+            }
+            // CRITICAL: check the state during registration so that the last
+            // reader leaving between try_acquire_write() and registering (it saw
+            // waiting_writers > 0 and woke a writer) is not missed
+            let state = &self.state;
+            self.writers
+                .add_waiter_if(|| state.load(Ordering::SeqCst) == 0)
+                .await;
+        }
+    }
+
+    fn release_read(&self) {
+        let previous = self.state.fetch_sub(1, Ordering::SeqCst);
+        if previous == 1 && self.waiting_writers.load(Ordering::SeqCst) > 0 {
+            self.writers.wake_one();
+        }
+    }
+
+    fn release_write(&self) {
+        self.state.store(0, Ordering::SeqCst);
+        self.wake_after_write();
+    }
+
+    /// Convert the held write lock into a single read lock
+    fn downgrade(&self) {
+        self.state.store(1, Ordering::SeqCst);
+        // Other readers may join unless a writer is waiting
+        if self.waiting_writers.load(Ordering::SeqCst) == 0 {
+            self.readers.wake_all();
+        }
+    }
+
+    /// Hand the lock to the next writer if there is one, otherwise to readers
+    fn wake_after_write(&self) {
+        if self.waiting_writers.load(Ordering::SeqCst) > 0 {
+            self.writers.wake_one();
+        } else {
+            self.readers.wake_all();
+        }
+    }
+}
+
+/// A writer registered in `waiting_writers` for the duration of `write()`
+struct WaitingWriter<'a, T, W: WaiterQueueTrait> {
+    lock: &'a RwLockGeneric<T, W>,
+}
+
+impl<'a, T, W: WaiterQueueTrait> WaitingWriter<'a, T, W> {
+    fn new(lock: &'a RwLockGeneric<T, W>) -> Self {
+        lock.waiting_writers.fetch_add(1, Ordering::SeqCst);
+        Self { lock }
+    }
+}
+
+impl<'a, T, W: WaiterQueueTrait> Drop for WaitingWriter<'a, T, W> {
+    fn drop(&mut self) {
+        let remaining = self.lock.waiting_writers.fetch_sub(1, Ordering::SeqCst) - 1;
+        if self.lock.state.load(Ordering::SeqCst) & WRITER != 0 {
+            // We hold the lock (or another writer does): its release wakes others
+            return;
+        }
+        // Cancelled while the lock is not write-held. We may have consumed the
+        // wake meant for the next writer, and readers may have been waiting
+        // only because of us; pass both on.
+        if remaining > 0 {
+            if self.lock.state.load(Ordering::SeqCst) == 0 {
+                self.lock.writers.wake_one();
+            }
+        } else {
+            self.lock.readers.wake_all();
+        }
+    }
+}
+
+impl<T: Default, W: WaiterQueueTrait> Default for RwLockGeneric<T, W> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T, W: WaiterQueueTrait> From<T> for RwLockGeneric<T, W> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for RwLockGeneric<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(_) => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// RAII guard for shared read access to an [`RwLock`]
+pub struct RwLockReadGuard<'a, T, W: WaiterQueueTrait> {
+    lock: &'a RwLockGeneric<T, W>,
+}
+
+impl<'a, T, W: WaiterQueueTrait> Deref for RwLockReadGuard<'a, T, W> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: a read lock is held, so no writer can access the data
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T, W: WaiterQueueTrait> Drop for RwLockReadGuard<'a, T, W> {
+    fn drop(&mut self) {
+        self.lock.release_read();
+    }
+}
+
+impl<'a, T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for RwLockReadGuard<'a, T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// RAII guard for exclusive write access to an [`RwLock`]
+pub struct RwLockWriteGuard<'a, T, W: WaiterQueueTrait> {
+    lock: &'a RwLockGeneric<T, W>,
+}
+
+// SAFETY: sharing the guard only hands out `&T`
+unsafe impl<'a, T: Sync, W: WaiterQueueTrait> Sync for RwLockWriteGuard<'a, T, W> {}
+
+impl<'a, T, W: WaiterQueueTrait> RwLockWriteGuard<'a, T, W> {
+    /// Atomically convert this write guard into a read guard
+    ///
+    /// No writer can slip in between. Other waiting readers are admitted unless
+    /// a writer is also waiting.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::{RwLock, RwLockWriteGuard};
+    ///
+    /// # async fn example() {
+    /// let lock = RwLock::new(Vec::new());
+    /// let mut writer = lock.write().await;
+    /// writer.push(1);
+    /// let reader = RwLockWriteGuard::downgrade(writer);
+    /// assert_eq!(*reader, [1]);
+    /// # }
+    /// ```
+    pub fn downgrade(this: Self) -> RwLockReadGuard<'a, T, W> {
+        let lock = this.lock;
+        std::mem::forget(this);
+        lock.downgrade();
+        RwLockReadGuard { lock }
+    }
+}
+
+impl<'a, T, W: WaiterQueueTrait> Deref for RwLockWriteGuard<'a, T, W> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the write lock is held
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T, W: WaiterQueueTrait> DerefMut for RwLockWriteGuard<'a, T, W> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the write lock is held
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T, W: WaiterQueueTrait> Drop for RwLockWriteGuard<'a, T, W> {
+    fn drop(&mut self) {
+        self.lock.release_write();
+    }
+}
+
+impl<'a, T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for RwLockWriteGuard<'a, T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Owned read guard for an [`RwLock`], obtained through an `Arc`
+pub struct OwnedRwLockReadGuard<T, W: WaiterQueueTrait> {
+    lock: Arc<RwLockGeneric<T, W>>,
+}
+
+impl<T, W: WaiterQueueTrait> OwnedRwLockReadGuard<T, W> {
+    /// Get the lock this guard holds
+    pub fn rwlock(this: &Self) -> &Arc<RwLockGeneric<T, W>> {
+        &this.lock
+    }
+}
+
+impl<T, W: WaiterQueueTrait> Deref for OwnedRwLockReadGuard<T, W> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: a read lock is held, so no writer can access the data
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T, W: WaiterQueueTrait> Drop for OwnedRwLockReadGuard<T, W> {
+    fn drop(&mut self) {
+        self.lock.release_read();
+    }
+}
+
+impl<T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for OwnedRwLockReadGuard<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Owned write guard for an [`RwLock`], obtained through an `Arc`
+pub struct OwnedRwLockWriteGuard<T, W: WaiterQueueTrait> {
+    lock: Arc<RwLockGeneric<T, W>>,
+}
+
+impl<T, W: WaiterQueueTrait> OwnedRwLockWriteGuard<T, W> {
+    /// Atomically convert this write guard into an owned read guard
+    pub fn downgrade(this: Self) -> OwnedRwLockReadGuard<T, W> {
+        // SAFETY: `this` is forgotten right after, so the Arc is moved out once
+        let lock = unsafe { std::ptr::read(&this.lock) };
+        std::mem::forget(this);
+        lock.downgrade();
+        OwnedRwLockReadGuard { lock }
+    }
+
+    /// Get the lock this guard holds
+    pub fn rwlock(this: &Self) -> &Arc<RwLockGeneric<T, W>> {
+        &this.lock
+    }
+}
+
+impl<T, W: WaiterQueueTrait> Deref for OwnedRwLockWriteGuard<T, W> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the write lock is held
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T, W: WaiterQueueTrait> DerefMut for OwnedRwLockWriteGuard<T, W> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the write lock is held
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T, W: WaiterQueueTrait> Drop for OwnedRwLockWriteGuard<T, W> {
+    fn drop(&mut self) {
+        self.lock.release_write();
+    }
+}
+
+impl<T: fmt::Debug, W: WaiterQueueTrait> fmt::Debug for OwnedRwLockWriteGuard<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readers_share_writer_excludes() {
+        let lock = RwLock::new(1);
+        let r1 = lock.try_read().unwrap();
+        let r2 = lock.try_read().unwrap();
+        assert_eq!(*r1 + *r2, 2);
+        assert!(lock.try_write().is_err());
+
+        drop(r1);
+        drop(r2);
+        let mut w = lock.try_write().unwrap();
+        *w = 5;
+        assert!(lock.try_read().is_err());
+        assert!(lock.try_write().is_err());
+        drop(w);
+
+        assert_eq!(lock.into_inner(), 5);
+    }
+
+    #[test]
+    fn test_downgrade_keeps_writers_out() {
+        let lock = RwLock::new(String::new());
+        let mut w = lock.try_write().unwrap();
+        w.push('x');
+
+        let r = RwLockWriteGuard::downgrade(w);
+        assert_eq!(*r, "x");
+        assert!(lock.try_write().is_err());
+        // No writer waiting: other readers may join
+        assert!(lock.try_read().is_ok());
+
+        drop(r);
+        assert!(lock.try_write().is_ok());
+    }
+
+    #[test]
+    fn test_owned_guards() {
+        let lock = Arc::new(RwLock::new(vec![1]));
+        let mut w = lock.clone().try_write_owned().unwrap();
+        w.push(2);
+        let r = OwnedRwLockWriteGuard::downgrade(w);
+        assert!(Arc::ptr_eq(OwnedRwLockReadGuard::rwlock(&r), &lock));
+
+        let r2 = lock.clone().try_read_owned().unwrap();
+        assert_eq!(*r, *r2);
+        drop((r, r2));
+        assert!(lock.try_write().is_ok());
+    }
+
+    #[compio::test]
+    async fn test_waiting_writer_blocks_new_readers() {
+        let lock = Arc::new(RwLock::new(0));
+        let reader = lock.try_read().unwrap();
+
+        let writer = {
+            let lock = lock.clone();
+            compio::runtime::spawn(async move {
+                *lock.write().await += 1;
+            })
+        };
+        compio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        // Writer preference: a new reader must not jump ahead
+        assert!(lock.try_read().is_err());
+
+        drop(reader);
+        writer.await.unwrap();
+        assert_eq!(*lock.try_read().unwrap(), 1);
+    }
+}
@@ -15,7 +15,7 @@
 //! let semaphore = Arc::new(Semaphore::new(1024));
 //!
 //! // Acquire permit before starting work
-//! let permit = semaphore.acquire().await;
+//! let permit = semaphore.acquire().await?;
 //!
 //! // Do work while holding permit
 //! // ...
@@ -27,12 +27,20 @@
 //! ```
 
 use crate::waiter_queue::{WaiterQueue, WaiterQueueTrait};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use atomic_waker::AtomicWaker;
+use parking_lot::Mutex;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Wake, Waker};
+use std::time::{Duration, Instant};
 
 /// A compio-compatible async semaphore for bounding concurrency
 ///
-/// The semaphore maintains a fixed number of permits that must be acquired
-/// before performing an operation. When all permits are in use, `acquire()`
+/// The semaphore maintains a number of permits that must be acquired
+/// before performing an operation (resizable with `set_max_permits()`). When all permits are in use, `acquire()`
 /// will wait asynchronously until a permit becomes available.
 ///
 /// # Design
@@ -56,7 +64,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 /// for i in 0..1000 {
 ///     let sem = sem.clone();
 ///     compio::runtime::spawn(async move {
-///         let _permit = sem.acquire().await;
+///         let _permit = sem.acquire().await.unwrap();
 ///         // Only 100 tasks run concurrently
 ///         println!("Processing {}", i);
 ///     });
@@ -82,16 +90,37 @@ pub type Semaphore = SemaphoreGeneric<WaiterQueue>;
 ///
 /// # Implementation Note
 ///
-/// Currently uses `Mutex<VecDeque<Waker>>` for simplicity and maintainability.
-///
-/// **Future optimization**: Could use intrusive linked list (like tokio) to avoid
-/// allocations and improve cache locality. However, this requires unsafe code and
-/// is significantly more complex. The current VecDeque approach is proven and fast enough.
+/// Waiters are parked in the `W` waiter queue. The generic implementation keeps
+/// a lock-free single-waiter slot plus an intrusive `WaiterList` of nodes that
+/// live in the pending acquire futures (like tokio), so queueing does not
+/// allocate and a cancelled acquire unlinks itself in O(1).
 struct SemaphoreInner<W: WaiterQueueTrait> {
     /// Available permits (atomic for lock-free operations)
     permits: AtomicUsize,
-    /// Maximum permits (for metrics and debugging)
-    max_permits: usize,
+    /// Maximum permits (current configured capacity)
+    max_permits: AtomicUsize,
+    /// Permits still owed by a shrink that could not take them from the pool
+    ///
+    /// Released permits pay this down before returning to the pool, so capacity
+    /// shrinks lazily as holders finish.
+    pending_shrink: AtomicUsize,
+    /// Serializes capacity changes so `max_permits` and `pending_shrink` move together
+    ///
+    /// Only resizing takes this lock; acquire and release stay lock-free.
+    resize_lock: Mutex<()>,
+    /// Permits reserved for the oldest queued multi-permit waiter (0 = none)
+    ///
+    /// Other acquirers must leave this many permits available, so a large
+    /// request is not starved by a stream of small ones. A reservation larger
+    /// than `max_permits` (after a shrink) is ignored until capacity grows back.
+    reserved: AtomicUsize,
+    /// Number of queued waiters requesting more than one permit
+    ///
+    /// While non-zero, waiters have different demands and a single `wake_one()`
+    /// may pick one that still cannot proceed, so releases wake everyone instead.
+    many_waiters: AtomicUsize,
+    /// Whether the semaphore has been closed (no further permits are handed out)
+    closed: AtomicBool,
     /// Waiter queue abstraction (handles mutex + wait/wake pattern)
     /// See `waiter_queue.rs` for why mutex is safe in async code
     waiters: W,
@@ -122,7 +151,12 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
         Self {
             inner: SemaphoreInner {
                 permits: AtomicUsize::new(permits),
-                max_permits: permits,
+                max_permits: AtomicUsize::new(permits),
+                pending_shrink: AtomicUsize::new(0),
+                resize_lock: Mutex::new(()),
+                reserved: AtomicUsize::new(0),
+                many_waiters: AtomicUsize::new(0),
+                closed: AtomicBool::new(false),
                 waiters: W::new(),
             },
         }
@@ -133,6 +167,11 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
     /// Returns a `SemaphorePermit` that will release the permit when dropped.
     /// This method will wait (yield to other tasks) if no permits are currently available.
     ///
+    /// # Errors
+    ///
+    /// Returns [`AcquireError`] if the semaphore is closed, either before the call
+    /// or while waiting.
+    ///
     /// # Example
     ///
     /// ```rust,no_run
@@ -141,67 +180,473 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
     /// # async fn example() {
     /// let sem = Semaphore::new(10);
     ///
-    /// let permit = sem.acquire().await;
+    /// let permit = sem.acquire().await.unwrap();
     /// // Do work...
     /// drop(permit);  // Release permit
     /// # }
     /// ```
-    pub async fn acquire(&self) -> SemaphorePermit<'_, W> {
+    pub async fn acquire(&self) -> Result<SemaphorePermit<'_, W>, AcquireError> {
+        self.acquire_many(1).await
+    }
+
+    /// Acquire `n` permits at once, waiting asynchronously until all are available
+    ///
+    /// Returns a single `SemaphorePermit` that tracks its count and releases all
+    /// `n` permits when dropped. Useful for weighted resources such as bytes of
+    /// buffer memory or operations needing several file descriptors.
+    ///
+    /// Permits are taken all-or-nothing. To keep a large request from being
+    /// starved by a stream of small ones, the first queued multi-permit waiter
+    /// reserves its count: while the reservation is held, other acquirers only
+    /// succeed if they leave at least that many permits available.
+    ///
+    /// A request larger than [`max_permits`](Self::max_permits) never reserves
+    /// permits (so it cannot block smaller requests) and waits until capacity
+    /// is grown with [`set_max_permits`](Self::set_max_permits). The same holds
+    /// if capacity is shrunk below a reservation that is already held: it is
+    /// ignored until capacity grows back.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcquireError`] if the semaphore is closed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::Semaphore;
+    ///
+    /// # async fn example() {
+    /// // 64 KiB of buffer memory, accounted in 1 KiB units
+    /// let sem = Semaphore::new(64);
+    ///
+    /// let permit = sem.acquire_many(16).await.unwrap();
+    /// assert_eq!(permit.num_permits(), 16);
+    /// assert_eq!(sem.available_permits(), 48);
+    ///
+    /// drop(permit);  // Returns all 16 permits
+    /// # }
+    /// ```
+    pub async fn acquire_many(&self, n: usize) -> Result<SemaphorePermit<'_, W>, AcquireError> {
+        self.acquire_inner(n).await?;
+        Ok(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Acquire an owned permit, waiting asynchronously if none are available
+    ///
+    /// Like [`acquire`](Self::acquire), but takes an `Arc<Semaphore>` and returns
+    /// an [`OwnedSemaphorePermit`] that keeps the semaphore alive. The permit is
+    /// `'static`, so it can be moved into spawned tasks or stored in structs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcquireError`] if the semaphore is closed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::Semaphore;
+    /// use std::sync::Arc;
+    ///
+    /// # async fn example() {
+    /// let sem = Arc::new(Semaphore::new(10));
+    ///
+    /// for i in 0..100 {
+    ///     // Backpressure happens here, in the spawning loop
+    ///     let permit = sem.clone().acquire_owned().await.unwrap();
+    ///     compio::runtime::spawn(async move {
+    ///         println!("Processing {}", i);
+    ///         drop(permit);
+    ///     })
+    ///     .detach();
+    /// }
+    /// # }
+    /// ```
+    pub async fn acquire_owned(self: Arc<Self>) -> Result<OwnedSemaphorePermit<W>, AcquireError> {
+        self.acquire_many_owned(1).await
+    }
+
+    /// Acquire `n` owned permits at once, waiting asynchronously until all are available
+    ///
+    /// Combines [`acquire_many`](Self::acquire_many) and
+    /// [`acquire_owned`](Self::acquire_owned).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcquireError`] if the semaphore is closed.
+    pub async fn acquire_many_owned(
+        self: Arc<Self>,
+        n: usize,
+    ) -> Result<OwnedSemaphorePermit<W>, AcquireError> {
+        self.acquire_inner(n).await?;
+        Ok(OwnedSemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Wait until `n` permits have been taken from the counter
+    ///
+    /// Shared by the borrowed and owned acquire paths; the caller is responsible
+    /// for wrapping the taken permits in a guard.
+    async fn acquire_inner(&self, n: usize) -> Result<(), AcquireError> {
+        if self.is_closed() {
+            return Err(AcquireError(()));
+        }
+
+        // Fast path: try to acquire immediately
+        if self.try_take(n, false) {
+            return Ok(());
+        }
+
+        // Slow path: track this waiter so releases know how to wake, and so a
+        // cancelled reservation is handed back on drop
+        let mut waiter = ManyWaiter::new(self, n);
+
         loop {
-            // Fast path: try to acquire immediately
-            if let Some(permit) = self.try_acquire() {
-                return permit;
+            if self.is_closed() {
+                return Err(AcquireError(()));
+            }
+
+            // Oldest multi-permit waiter claims the reservation so that smaller
+            // requests can no longer drain the permits out from under it.
+            // Requests that cannot fit in the current capacity never reserve.
+            if n > 1 && !waiter.reserved && n <= self.max_permits() {
+                waiter.reserved = self
+                    .inner
+                    .reserved
+                    .compare_exchange(0, n, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok();
+            }
+
+            if self.try_take(n, waiter.reserved) {
+                waiter.complete();
+                return Ok(());
             }
 
-            // No permits - register waiter and wait for release
+            // Not enough permits - register waiter and wait for release or close
             // CRITICAL: Check permit availability during registration to prevent lost-wake race
-            // If permits become available after try_acquire() fails but before registration
+            // If permits become available after try_take() fails but before registration
             // completes, the condition re-check will catch it and return immediately.
-            self.inner
+            // The same applies to close(), which must never leave a waiter parked.
+            let reserved = waiter.reserved;
+            let wait = self
+                .inner
                 .waiters
-                .add_waiter_if(|| self.available_permits() > 0)
+                .add_waiter_if(|| self.is_closed() || self.can_take(n, reserved));
+            if self.inner.waiters.forwards_cancelled_wakes() {
+                // A wake swallowed by dropping this future is passed on by the queue
+                wait.await;
+            } else {
+                // The queue is handed a waker that records the wake, so a future
+                // dropped after being woken knows whether it swallowed one
+                let signal = waiter.park();
+                let waker = Waker::from(signal.clone());
+                let mut wait = pin!(wait);
+                poll_fn(|cx| {
+                    signal.task.register(cx.waker());
+                    wait.as_mut().poll(&mut Context::from_waker(&waker))
+                })
                 .await;
+                waiter.waiting = false;
+            }
 
-            // After wake (or immediate return), loop back to try_acquire
+            // After wake (or immediate return), loop back to try_take
+        }
+    }
+
+    /// Acquire a permit, giving up after `timeout` has elapsed
+    ///
+    /// Equivalent to [`acquire_until`](Self::acquire_until) with a deadline of
+    /// `Instant::now() + timeout`.
+    ///
+    /// # Errors
+    ///
+    /// - [`AcquireTimeout::Elapsed`] if no permit became available in time
+    /// - [`AcquireTimeout::Closed`] if the semaphore is closed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use compio_sync::{AcquireTimeout, Semaphore};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() {
+    /// let sem = Semaphore::new(1);
+    /// let _held = sem.acquire().await.unwrap();
+    ///
+    /// let result = sem.acquire_timeout(Duration::from_millis(10)).await;
+    /// assert_eq!(result.err(), Some(AcquireTimeout::Elapsed { queued: true }));
+    /// # }
+    /// ```
+    pub async fn acquire_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<SemaphorePermit<'_, W>, AcquireTimeout> {
+        self.acquire_until(Instant::now() + timeout).await
+    }
+
+    /// Acquire a permit, giving up once `deadline` is reached
+    ///
+    /// Unlike wrapping [`acquire`](Self::acquire) in `compio::time::timeout`,
+    /// the error reports whether the deadline passed while this task was queued
+    /// behind other waiters or before it ever had to wait. On expiry the pending
+    /// acquire is dropped, which deregisters its waiter from the queue.
+    ///
+    /// # Errors
+    ///
+    /// - [`AcquireTimeout::Elapsed`] if no permit became available in time
+    /// - [`AcquireTimeout::Closed`] if the semaphore is closed
+    pub async fn acquire_until(
+        &self,
+        deadline: Instant,
+    ) -> Result<SemaphorePermit<'_, W>, AcquireTimeout> {
+        self.acquire_inner_until(1, deadline).await?;
+        Ok(SemaphorePermit {
+            semaphore: self,
+            permits: 1,
+        })
+    }
+
+    /// Wait until `n` permits have been taken or `deadline` is reached
+    async fn acquire_inner_until(&self, n: usize, deadline: Instant) -> Result<(), AcquireTimeout> {
+        // Fast path: never queued, so a passed deadline is reported as such
+        match self.try_acquire_inner(n) {
+            Ok(()) => return Ok(()),
+            Err(TryAcquireError::Closed) => return Err(AcquireTimeout::Closed),
+            Err(TryAcquireError::NoPermits) => {}
+        }
+        if Instant::now() >= deadline {
+            return Err(AcquireTimeout::Elapsed { queued: false });
+        }
+
+        // Slow path: on expiry timeout_at drops the acquire future, whose
+        // waiter registration is removed from the queue as part of the drop
+        match compio::time::timeout_at(deadline, self.acquire_inner(n)).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(AcquireError(()))) => Err(AcquireTimeout::Closed),
+            Err(_elapsed) => Err(AcquireTimeout::Elapsed { queued: true }),
         }
     }
 
     /// Try to acquire a permit without waiting
     ///
-    /// Returns `Some(SemaphorePermit)` if a permit was immediately available,
-    /// or `None` if all permits are currently in use.
+    /// Returns a `SemaphorePermit` if a permit was immediately available.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryAcquireError::Closed`] if the semaphore is closed
+    /// - [`TryAcquireError::NoPermits`] if all permits are currently in use
     ///
     /// # Example
     ///
     /// ```rust
-    /// use compio_sync::Semaphore;
+    /// use compio_sync::{Semaphore, TryAcquireError};
     ///
     /// let sem = Semaphore::new(1);
     ///
     /// let permit1 = sem.try_acquire();
-    /// assert!(permit1.is_some());
+    /// assert!(permit1.is_ok());
     ///
     /// let permit2 = sem.try_acquire();
-    /// assert!(permit2.is_none());  // No permits left
+    /// assert_eq!(permit2.err(), Some(TryAcquireError::NoPermits));  // No permits left
+    /// ```
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_, W>, TryAcquireError> {
+        self.try_acquire_many(1)
+    }
+
+    /// Try to acquire `n` permits at once without waiting
+    ///
+    /// Returns a `SemaphorePermit` holding all `n` permits if they are all
+    /// immediately available. Never takes a partial amount.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryAcquireError::Closed`] if the semaphore is closed
+    /// - [`TryAcquireError::NoPermits`] if fewer than `n` permits are available
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(4);
+    ///
+    /// let permit = sem.try_acquire_many(3).unwrap();
+    /// assert_eq!(permit.num_permits(), 3);
+    ///
+    /// assert!(sem.try_acquire_many(2).is_err());  // Only 1 left
+    /// assert_eq!(sem.available_permits(), 1);
+    /// ```
+    pub fn try_acquire_many(&self, n: usize) -> Result<SemaphorePermit<'_, W>, TryAcquireError> {
+        self.try_acquire_inner(n)?;
+        Ok(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Try to acquire an owned permit without waiting
+    ///
+    /// Like [`try_acquire`](Self::try_acquire), but takes an `Arc<Semaphore>` and
+    /// returns a `'static` [`OwnedSemaphorePermit`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`try_acquire`](Self::try_acquire).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::Semaphore;
+    /// use std::sync::Arc;
+    ///
+    /// let sem = Arc::new(Semaphore::new(1));
+    ///
+    /// let permit = sem.clone().try_acquire_owned();
+    /// assert!(permit.is_ok());
+    /// assert!(sem.clone().try_acquire_owned().is_err());
+    ///
+    /// drop(permit);
+    /// assert_eq!(sem.available_permits(), 1);
+    /// ```
+    pub fn try_acquire_owned(self: Arc<Self>) -> Result<OwnedSemaphorePermit<W>, TryAcquireError> {
+        self.try_acquire_many_owned(1)
+    }
+
+    /// Try to acquire `n` owned permits at once without waiting
+    ///
+    /// Combines [`try_acquire_many`](Self::try_acquire_many) and
+    /// [`try_acquire_owned`](Self::try_acquire_owned).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`try_acquire_many`](Self::try_acquire_many).
+    pub fn try_acquire_many_owned(
+        self: Arc<Self>,
+        n: usize,
+    ) -> Result<OwnedSemaphorePermit<W>, TryAcquireError> {
+        self.try_acquire_inner(n)?;
+        Ok(OwnedSemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Take `n` permits without waiting, reporting why if that is not possible
+    fn try_acquire_inner(&self, n: usize) -> Result<(), TryAcquireError> {
+        if self.is_closed() {
+            Err(TryAcquireError::Closed)
+        } else if self.try_take(n, false) {
+            Ok(())
+        } else {
+            Err(TryAcquireError::NoPermits)
+        }
+    }
+
+    /// Close the semaphore
+    ///
+    /// After closing, no new permits are handed out: every task currently
+    /// waiting in `acquire()` is woken and returns [`AcquireError`], and later
+    /// calls fail immediately. Permits that are already held stay valid and are
+    /// still returned to the pool when dropped.
+    ///
+    /// Closing is permanent; calling `close()` again has no further effect.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::{Semaphore, TryAcquireError};
+    ///
+    /// let sem = Semaphore::new(2);
+    /// let permit = sem.try_acquire().unwrap();
+    ///
+    /// sem.close();
+    /// assert!(sem.is_closed());
+    /// assert_eq!(sem.try_acquire().err(), Some(TryAcquireError::Closed));
+    ///
+    /// drop(permit);
+    /// assert_eq!(sem.available_permits(), 2);
     /// ```
+    pub fn close(&self) {
+        // SeqCst so waiters registering concurrently either observe the flag in
+        // their condition re-check or are already parked and get woken below
+        self.inner.closed.store(true, Ordering::SeqCst);
+        self.inner.waiters.wake_all();
+    }
+
+    /// Check whether the semaphore has been closed
     #[must_use]
-    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_, W>> {
-        // Fast path: atomic decrement if permits available
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Check whether `n` permits could be taken right now
+    ///
+    /// Unless the caller holds the reservation, permits reserved for the oldest
+    /// multi-permit waiter are not counted as available.
+    fn can_take(&self, n: usize, holds_reservation: bool) -> bool {
+        let reserved = if holds_reservation {
+            0
+        } else {
+            self.effective_reservation()
+        };
+        self.available_permits() >= n.saturating_add(reserved)
+    }
+
+    /// Permits held back for the reserving waiter
+    ///
+    /// A reservation that no longer fits in the capacity (after a shrink) could
+    /// never be satisfied, so it must not hold back anyone else.
+    fn effective_reservation(&self) -> usize {
+        let reserved = self.inner.reserved.load(Ordering::Acquire);
+        if reserved > self.max_permits() {
+            0
+        } else {
+            reserved
+        }
+    }
+
+    /// Wake everyone if a shrink just made the reservation unsatisfiable
+    ///
+    /// Waiters parked behind it may be able to proceed with the permits that
+    /// are already available.
+    fn wake_if_reservation_oversized(&self) {
+        if self.inner.reserved.load(Ordering::Acquire) > self.max_permits() {
+            self.inner.waiters.wake_all();
+        }
+    }
+
+    /// Atomically take `n` permits from the counter if enough are available
+    ///
+    /// Generalises the single-permit CAS loop: succeeds only if `n` permits (plus
+    /// any reservation held by another waiter) are available at once.
+    fn try_take(&self, n: usize, holds_reservation: bool) -> bool {
+        let reserved = if holds_reservation {
+            0
+        } else {
+            self.effective_reservation()
+        };
+        let needed = n.saturating_add(reserved);
+
+        // Fast path: atomic subtract if enough permits available
         let mut current = self.inner.permits.load(Ordering::Acquire);
 
         loop {
-            if current == 0 {
-                return None; // No permits available
+            if current < needed {
+                return false; // Not enough permits available
             }
 
-            // Try to atomically decrement
+            // Try to atomically subtract
             match self.inner.permits.compare_exchange_weak(
                 current,
-                current - 1,
+                current - n,
                 Ordering::AcqRel,
                 Ordering::Acquire,
             ) {
-                Ok(_) => return Some(SemaphorePermit { semaphore: self }),
+                Ok(_) => return true,
                 Err(actual) => current = actual, // Retry with updated value
             }
         }
@@ -230,6 +675,10 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
 
     /// Get the maximum number of permits (configured limit)
     ///
+    /// Reflects [`set_max_permits`](Self::set_max_permits),
+    /// [`add_permits`](Self::add_permits), [`reduce_permits`](Self::reduce_permits)
+    /// and forgotten permits.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -240,10 +689,14 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
     /// ```
     #[must_use]
     pub fn max_permits(&self) -> usize {
-        self.inner.max_permits
+        self.inner.max_permits.load(Ordering::Acquire)
     }
 
-    /// Get the number of permits currently in use (max - available)
+    /// Get the number of permits currently in use
+    ///
+    /// Counts every permit held by a guard. After a shrink this may briefly
+    /// exceed [`max_permits`](Self::max_permits) until the excess holders
+    /// release their permits.
     ///
     /// # Example
     ///
@@ -256,35 +709,97 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
     /// ```
     #[must_use]
     pub fn in_use(&self) -> usize {
-        self.inner.max_permits - self.available_permits()
+        // held + available == max + pending_shrink
+        (self.max_permits() + self.inner.pending_shrink.load(Ordering::Acquire))
+            .saturating_sub(self.available_permits())
     }
 
-    /// Reduce the number of available permits (for adaptive concurrency control)
+    /// Change the maximum number of permits
     ///
-    /// This allows dynamically reducing concurrency in response to resource constraints
-    /// like file descriptor exhaustion. Only reduces permits that are currently available
-    /// (won't affect permits already in use).
+    /// Growing takes effect immediately: the new permits are added to the pool
+    /// and waiters are woken. Shrinking removes available permits right away and
+    /// absorbs the remainder lazily as held permits are released, so no holder
+    /// is ever revoked. [`max_permits`](Self::max_permits) reports the new
+    /// value immediately in both cases.
     ///
-    /// # Arguments
+    /// # Panics
     ///
-    /// * `count` - Number of permits to remove from the available pool
+    /// Panics if `permits` is 0 (semaphore must have at least one permit)
     ///
-    /// # Returns
+    /// # Example
     ///
-    /// The actual number of permits reduced (may be less than requested if not enough available)
+    /// ```rust
+    /// use compio_sync::Semaphore;
     ///
-    /// # Examples
+    /// let sem = Semaphore::new(4);
+    /// let held = sem.try_acquire_many(3).unwrap();
     ///
-    /// ```
-    /// use compio_sync::Semaphore;
+    /// // Shrink below what is held: the free permit goes now, the rest on release
+    /// sem.set_max_permits(2);
+    /// assert_eq!(sem.max_permits(), 2);
+    /// assert_eq!(sem.available_permits(), 0);
+    /// assert_eq!(sem.in_use(), 3);
     ///
-    /// let sem = Semaphore::new(100);
-    /// let reduced = sem.reduce_permits(20);
-    /// assert_eq!(reduced, 20);
-    /// assert_eq!(sem.available_permits(), 80);
+    /// drop(held);
+    /// assert_eq!(sem.available_permits(), 2);
+    /// assert_eq!(sem.in_use(), 0);
+    ///
+    /// sem.set_max_permits(8);
+    /// assert_eq!(sem.available_permits(), 8);
     /// ```
-    #[must_use]
-    pub fn reduce_permits(&self, count: usize) -> usize {
+    pub fn set_max_permits(&self, permits: usize) {
+        assert!(permits > 0, "Semaphore must have at least one permit");
+
+        let _guard = self.inner.resize_lock.lock();
+        let current = self.inner.max_permits.load(Ordering::Acquire);
+        if permits >= current {
+            self.grow(permits - current);
+        } else {
+            let shrink = current - permits;
+            self.inner.max_permits.fetch_sub(shrink, Ordering::AcqRel);
+            let taken = self.take_available(shrink);
+            self.inner
+                .pending_shrink
+                .fetch_add(shrink - taken, Ordering::AcqRel);
+            self.wake_if_reservation_oversized();
+        }
+    }
+
+    /// Grow capacity by `count` (caller holds `resize_lock`)
+    ///
+    /// Cancels any outstanding shrink first, then adds the rest to the pool.
+    fn grow(&self, count: usize) {
+        self.inner.max_permits.fetch_add(count, Ordering::AcqRel);
+        let cancelled = self.absorb_pending_shrink(count);
+        let added = count - cancelled;
+        if added > 0 {
+            self.inner.permits.fetch_add(added, Ordering::SeqCst);
+            self.wake_for_released(added);
+        }
+    }
+
+    /// Pay down up to `count` of the outstanding shrink, returning the amount absorbed
+    fn absorb_pending_shrink(&self, count: usize) -> usize {
+        let mut current = self.inner.pending_shrink.load(Ordering::Acquire);
+        loop {
+            if current == 0 || count == 0 {
+                return 0;
+            }
+            let absorbed = std::cmp::min(current, count);
+            match self.inner.pending_shrink.compare_exchange_weak(
+                current,
+                current - absorbed,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return absorbed,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Remove up to `count` permits from the available pool, returning the amount taken
+    fn take_available(&self, count: usize) -> usize {
         let mut reduced = 0;
 
         loop {
@@ -309,9 +824,46 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
         reduced
     }
 
+    /// Reduce the number of available permits (for adaptive concurrency control)
+    ///
+    /// This allows dynamically reducing concurrency in response to resource constraints
+    /// like file descriptor exhaustion. Only reduces permits that are currently available
+    /// (won't affect permits already in use). The maximum is lowered by the
+    /// amount actually reduced.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of permits to remove from the available pool
+    ///
+    /// # Returns
+    ///
+    /// The actual number of permits reduced (may be less than requested if not enough available)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use compio_sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(100);
+    /// let reduced = sem.reduce_permits(20);
+    /// assert_eq!(reduced, 20);
+    /// assert_eq!(sem.available_permits(), 80);
+    /// assert_eq!(sem.max_permits(), 80);
+    /// ```
+    #[must_use]
+    pub fn reduce_permits(&self, count: usize) -> usize {
+        let _guard = self.inner.resize_lock.lock();
+        let reduced = self.take_available(count);
+        self.inner.max_permits.fetch_sub(reduced, Ordering::AcqRel);
+        self.wake_if_reservation_oversized();
+        reduced
+    }
+
     /// Add permits back to the semaphore (for adaptive concurrency control)
     ///
     /// This allows dynamically increasing concurrency after resources become available.
+    /// The maximum is raised by `count`; any shrink still waiting for held permits
+    /// to be released is cancelled first.
     ///
     /// # Arguments
     ///
@@ -328,32 +880,179 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
     ///
     /// sem.add_permits(20);
     /// assert_eq!(sem.available_permits(), 100);
+    /// assert_eq!(sem.max_permits(), 100);
     /// ```
     pub fn add_permits(&self, count: usize) {
-        self.inner.permits.fetch_add(count, Ordering::Release);
+        let _guard = self.inner.resize_lock.lock();
+        self.grow(count);
+    }
 
-        // Wake up waiters (up to count)
-        // Note: This could be optimized with a wake_n() method on WaiterQueue
-        for _ in 0..count {
-            self.inner.waiters.wake_one();
+    /// Permanently remove `count` held permits from capacity (see `forget()`)
+    fn forget_permits(&self, count: usize) {
+        if count == 0 {
+            return;
         }
+
+        // Forgotten permits first cover an outstanding shrink, then lower the max
+        let _guard = self.inner.resize_lock.lock();
+        let absorbed = self.absorb_pending_shrink(count);
+        let _ = self
+            .inner
+            .max_permits
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |max| {
+                Some(max.saturating_sub(count - absorbed))
+            });
+        self.wake_if_reservation_oversized();
     }
 
-    /// Release a permit (called internally by `SemaphorePermit::drop`)
-    fn release(&self) {
+    /// Release `count` permits (called internally when permit guards drop)
+    ///
+    /// Also used by the bounded mpsc channel, where a queued message holds the
+    /// permit of the guard that was leaked when it was sent.
+    pub(crate) fn release(&self, count: usize) {
+        // A pending shrink absorbs released permits before they reach the pool
+        let count = count - self.absorb_pending_shrink(count);
+        if count == 0 {
+            return;
+        }
+
         // Increment available permits
-        self.inner.permits.fetch_add(1, Ordering::Release);
+        self.inner.permits.fetch_add(count, Ordering::SeqCst);
+
+        // Wake waiters (WaiterQueue handles lock-then-wake pattern)
+        self.wake_for_released(count);
+    }
 
-        // Wake one waiter (WaiterQueue handles lock-then-wake pattern)
-        self.inner.waiters.wake_one();
+    /// Wake waiters after `count` permits were returned to the pool
+    ///
+    /// A single released permit with only single-permit waiters wakes exactly one
+    /// task. Otherwise waiters may have different demands (or several can now
+    /// proceed), so all are woken and re-check their own condition.
+    fn wake_for_released(&self, count: usize) {
+        // SeqCst pairs with ManyWaiter::new: either we see the multi-permit waiter
+        // here, or its registration condition sees the permits we just added.
+        if count == 1 && self.inner.many_waiters.load(Ordering::SeqCst) == 0 {
+            self.inner.waiters.wake_one();
+        } else {
+            self.inner.waiters.wake_all();
+        }
+    }
+}
+
+/// Bookkeeping for a queued `acquire_many()` call
+///
+/// Counts the waiter in `many_waiters` while it is queued and owns the
+/// reservation once claimed. If the acquire future is dropped before completing,
+/// the reservation is released and everyone is woken, since permits that were
+/// held back for this waiter are now usable by others.
+///
+/// A future dropped after `wake_one()` picked it but before it was polled
+/// (e.g. it lost a `select`) would swallow that wake. Unless the queue already
+/// forwards such wakes, the waiter parks with a [`WakeSignal`] and re-issues
+/// the wake when permits are free, so the permit does not sit idle while
+/// another waiter keeps sleeping.
+struct ManyWaiter<'a, W: WaiterQueueTrait> {
+    semaphore: &'a SemaphoreGeneric<W>,
+    /// Number of permits requested
+    n: usize,
+    /// Whether this waiter currently holds the reservation
+    reserved: bool,
+    /// Whether this waiter is parked with a wake signal (woken but not yet
+    /// acquired counts as parked until it is polled)
+    waiting: bool,
+    /// Records wakes delivered to this waiter, created when it first parks on
+    /// a queue that does not forward them itself
+    signal: Option<Arc<WakeSignal>>,
+}
+
+impl<'a, W: WaiterQueueTrait> ManyWaiter<'a, W> {
+    fn new(semaphore: &'a SemaphoreGeneric<W>, n: usize) -> Self {
+        if n > 1 {
+            semaphore.inner.many_waiters.fetch_add(1, Ordering::SeqCst);
+        }
+        Self {
+            semaphore,
+            n,
+            reserved: false,
+            waiting: false,
+            signal: None,
+        }
+    }
+
+    /// About to park: returns the wake signal, cleared of any earlier wake
+    fn park(&mut self) -> Arc<WakeSignal> {
+        self.waiting = true;
+        let signal = self.signal.get_or_insert_with(Default::default);
+        signal.woken.store(false, Ordering::Relaxed);
+        signal.clone()
+    }
+
+    /// Whether this waiter was woken while parked and never got to act on it
+    fn swallowed_wake(&self) -> bool {
+        self.waiting
+            && self
+                .signal
+                .as_ref()
+                .is_some_and(|signal| signal.woken.load(Ordering::Acquire))
+    }
+
+    /// Permits were taken: hand back the reservation without waking anyone
+    ///
+    /// Taking `n` permits and dropping an `n`-permit reservation leaves every
+    /// other waiter's slack unchanged, so no one new can proceed.
+    fn complete(&mut self) {
+        if self.reserved {
+            self.semaphore.inner.reserved.store(0, Ordering::Release);
+            self.reserved = false;
+        }
+    }
+}
+
+impl<'a, W: WaiterQueueTrait> Drop for ManyWaiter<'a, W> {
+    fn drop(&mut self) {
+        if self.n > 1 {
+            self.semaphore
+                .inner
+                .many_waiters
+                .fetch_sub(1, Ordering::SeqCst);
+        }
+
+        // Cancelled while holding the reservation: those permits are free again
+        if self.reserved {
+            self.semaphore.inner.reserved.store(0, Ordering::Release);
+            self.semaphore.inner.waiters.wake_all();
+        } else if self.swallowed_wake() && self.semaphore.available_permits() > 0 {
+            // Woken but never acquired: pass the wake on
+            self.semaphore.wake_for_released(1);
+        }
+    }
+}
+
+/// Waker given to the waiter queue by a parked acquire
+///
+/// Notes that a wake arrived, then wakes the task that polled last.
+#[derive(Default)]
+struct WakeSignal {
+    woken: AtomicBool,
+    task: AtomicWaker,
+}
+
+impl Wake for WakeSignal {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.task.wake();
     }
 }
 
 /// RAII guard that releases a semaphore permit on drop
 ///
-/// This guard is returned by `Semaphore::acquire()` and `Semaphore::try_acquire()`.
-/// When dropped, it automatically releases the permit back to the semaphore and
-/// wakes one waiting task (if any).
+/// This guard is returned by `Semaphore::acquire()` and `Semaphore::try_acquire()`
+/// (and their `_many` variants). When dropped, it automatically releases all of
+/// its permits back to the semaphore and wakes waiting tasks (if any).
 ///
 /// # Example
 ///
@@ -365,7 +1064,7 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
 /// let sem = Arc::new(Semaphore::new(10));
 ///
 /// {
-///     let permit = sem.acquire().await;
+///     let permit = sem.acquire().await.unwrap();
 ///     // Permit is held here
 /// } // Permit released automatically when scope ends
 ///
@@ -375,14 +1074,266 @@ impl<W: WaiterQueueTrait> SemaphoreGeneric<W> {
 pub struct SemaphorePermit<'a, W: WaiterQueueTrait> {
     /// Reference to the semaphore that issued this permit
     semaphore: &'a SemaphoreGeneric<W>,
+    /// Number of permits held by this guard
+    permits: usize,
+}
+
+impl<'a, W: WaiterQueueTrait> SemaphorePermit<'a, W> {
+    /// Get the number of permits held by this guard
+    #[must_use]
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Permanently consume the permits without returning them to the semaphore
+    ///
+    /// Use this to shrink capacity when the resource a permit stood for is lost
+    /// (e.g. a connection that cannot be re-established).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(4);
+    /// let permit = sem.try_acquire_many(2).unwrap();
+    ///
+    /// permit.forget();
+    /// assert_eq!(sem.available_permits(), 2);
+    /// assert_eq!(sem.max_permits(), 2);
+    /// ```
+    pub fn forget(mut self) {
+        self.semaphore.forget_permits(self.permits);
+        self.permits = 0;
+    }
+
+    /// Split off `n` permits into a new guard
+    ///
+    /// The returned guard borrows the same semaphore and releases its permits
+    /// independently, so part of a multi-permit can be handed to a child task.
+    /// Returns `None` (leaving `self` unchanged) if `n` exceeds the number of
+    /// permits held.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(4);
+    /// let mut permit = sem.try_acquire_many(3).unwrap();
+    ///
+    /// let child = permit.split(1).unwrap();
+    /// assert_eq!(permit.num_permits(), 2);
+    /// assert_eq!(child.num_permits(), 1);
+    ///
+    /// drop(child);
+    /// assert_eq!(sem.available_permits(), 2);
+    /// assert!(permit.split(3).is_none());
+    /// ```
+    #[must_use]
+    pub fn split(&mut self, n: usize) -> Option<Self> {
+        if n > self.permits {
+            return None;
+        }
+        self.permits -= n;
+        Some(Self {
+            semaphore: self.semaphore,
+            permits: n,
+        })
+    }
+
+    /// Merge the permits held by `other` into this guard
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` was issued by a different semaphore.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compio_sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(4);
+    /// let mut permit = sem.try_acquire().unwrap();
+    ///
+    /// permit.merge(sem.try_acquire_many(2).unwrap());
+    /// assert_eq!(permit.num_permits(), 3);
+    /// assert_eq!(sem.available_permits(), 1);
+    /// ```
+    pub fn merge(&mut self, mut other: Self) {
+        assert!(
+            std::ptr::eq(self.semaphore, other.semaphore),
+            "Cannot merge permits from different semaphores"
+        );
+        self.permits += other.permits;
+        other.permits = 0;
+    }
+}
+
+impl<'a, W: WaiterQueueTrait> Drop for SemaphorePermit<'a, W> {
+    fn drop(&mut self) {
+        self.semaphore.release(self.permits);
+    }
+}
+
+/// Owned RAII guard that releases a semaphore permit on drop
+///
+/// This guard is returned by `Semaphore::acquire_owned()` and
+/// `Semaphore::try_acquire_owned()`. It holds an `Arc` to the semaphore instead
+/// of a borrow, so it is `'static` and can be moved into spawned tasks or stored
+/// in long-lived structs. Dropping it releases the permit through the same path
+/// as [`SemaphorePermit`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use compio_sync::{OwnedSemaphorePermit, Semaphore, WaiterQueue};
+/// use std::sync::Arc;
+///
+/// struct Job {
+///     _permit: OwnedSemaphorePermit<WaiterQueue>,
+/// }
+///
+/// # async fn example() {
+/// let sem = Arc::new(Semaphore::new(10));
+/// let job = Job {
+///     _permit: sem.clone().acquire_owned().await.unwrap(),
+/// };
+/// assert_eq!(sem.available_permits(), 9);
+///
+/// drop(job);
+/// assert_eq!(sem.available_permits(), 10);
+/// # }
+/// ```
+pub struct OwnedSemaphorePermit<W: WaiterQueueTrait> {
+    /// Shared handle to the semaphore that issued this permit
+    semaphore: Arc<SemaphoreGeneric<W>>,
+    /// Number of permits held by this guard
+    permits: usize,
+}
+
+impl<W: WaiterQueueTrait> OwnedSemaphorePermit<W> {
+    /// Get the number of permits held by this guard
+    #[must_use]
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Permanently consume the permits without returning them to the semaphore
+    ///
+    /// See [`SemaphorePermit::forget`].
+    pub fn forget(mut self) {
+        self.semaphore.forget_permits(self.permits);
+        self.permits = 0;
+    }
+
+    /// Split off `n` permits into a new owned guard
+    ///
+    /// Returns `None` (leaving `self` unchanged) if `n` exceeds the number of
+    /// permits held. See [`SemaphorePermit::split`].
+    #[must_use]
+    pub fn split(&mut self, n: usize) -> Option<Self> {
+        if n > self.permits {
+            return None;
+        }
+        self.permits -= n;
+        Some(Self {
+            semaphore: Arc::clone(&self.semaphore),
+            permits: n,
+        })
+    }
+
+    /// Merge the permits held by `other` into this guard
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` was issued by a different semaphore.
+    pub fn merge(&mut self, mut other: Self) {
+        assert!(
+            Arc::ptr_eq(&self.semaphore, &other.semaphore),
+            "Cannot merge permits from different semaphores"
+        );
+        self.permits += other.permits;
+        other.permits = 0;
+    }
+
+    /// Get the semaphore that issued this permit
+    #[must_use]
+    pub fn semaphore(&self) -> &Arc<SemaphoreGeneric<W>> {
+        &self.semaphore
+    }
+}
+
+impl<W: WaiterQueueTrait> Drop for OwnedSemaphorePermit<W> {
+    fn drop(&mut self) {
+        self.semaphore.release(self.permits);
+    }
+}
+
+/// Error returned by `Semaphore::acquire()` when the semaphore has been closed
+///
+/// See `Semaphore::close()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcquireError(());
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("semaphore closed")
+    }
+}
+
+impl std::error::Error for AcquireError {}
+
+/// Error returned by `Semaphore::try_acquire()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryAcquireError {
+    /// The semaphore has been closed
+    Closed,
+    /// Not enough permits are currently available
+    NoPermits,
 }
 
-impl<'a, W: WaiterQueueTrait> Drop for SemaphorePermit<'a, W> {
-    fn drop(&mut self) {
-        self.semaphore.release();
+impl fmt::Display for TryAcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryAcquireError::Closed => f.write_str("semaphore closed"),
+            TryAcquireError::NoPermits => f.write_str("no permits available"),
+        }
+    }
+}
+
+impl std::error::Error for TryAcquireError {}
+
+/// Error returned by `Semaphore::acquire_timeout()` and `Semaphore::acquire_until()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcquireTimeout {
+    /// The semaphore has been closed
+    Closed,
+    /// The deadline passed before a permit became available
+    Elapsed {
+        /// Whether the task was queued waiting for a permit when the deadline
+        /// passed (`false` if the deadline had already passed when no permit
+        /// was immediately available)
+        queued: bool,
+    },
+}
+
+impl fmt::Display for AcquireTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AcquireTimeout::Closed => f.write_str("semaphore closed"),
+            AcquireTimeout::Elapsed { queued: true } => {
+                f.write_str("deadline elapsed while waiting for a permit")
+            }
+            AcquireTimeout::Elapsed { queued: false } => {
+                f.write_str("deadline elapsed before waiting for a permit")
+            }
+        }
     }
 }
 
+impl std::error::Error for AcquireTimeout {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -448,6 +1399,100 @@ mod tests {
         }
     }
 
+    /// FIFO waiter queue whose futures drop a wake they already received
+    ///
+    /// Unlike the generic queue, a woken future that is dropped before it
+    /// completes does not pass the wake on, so any forwarding has to come from
+    /// the semaphore itself. Counts the wakes it is asked for.
+    #[derive(Default)]
+    struct NonForwardingQueue {
+        /// Next waiter id and the waiters still parked
+        waiters: Mutex<(u64, std::collections::VecDeque<(u64, Waker)>)>,
+        wakes: AtomicUsize,
+    }
+
+    struct NonForwardingWait<'a> {
+        queue: &'a NonForwardingQueue,
+        condition: Box<dyn Fn() -> bool + Send + Sync + 'a>,
+        id: Option<u64>,
+    }
+
+    impl Future for NonForwardingWait<'_> {
+        type Output = ();
+
+        fn poll(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> std::task::Poll<()> {
+            let this = self.get_mut();
+            let mut waiters = this.queue.waiters.lock().unwrap();
+            match this.id {
+                None if (this.condition)() => std::task::Poll::Ready(()),
+                None => {
+                    waiters.0 += 1;
+                    let id = waiters.0;
+                    waiters.1.push_back((id, cx.waker().clone()));
+                    this.id = Some(id);
+                    std::task::Poll::Pending
+                }
+                Some(id) => match waiters.1.iter_mut().find(|(other, _)| *other == id) {
+                    Some((_, waker)) => {
+                        waker.clone_from(cx.waker());
+                        std::task::Poll::Pending
+                    }
+                    None => std::task::Poll::Ready(()),
+                },
+            }
+        }
+    }
+
+    impl Drop for NonForwardingWait<'_> {
+        fn drop(&mut self) {
+            if let Some(id) = self.id {
+                self.queue
+                    .waiters
+                    .lock()
+                    .unwrap()
+                    .1
+                    .retain(|(other, _)| *other != id);
+            }
+        }
+    }
+
+    impl WaiterQueueTrait for NonForwardingQueue {
+        fn new() -> Self {
+            Self::default()
+        }
+
+        fn add_waiter_if<'a, F>(&'a self, condition: F) -> impl std::future::Future<Output = ()>
+        where
+            F: Fn() -> bool + Send + Sync + 'a,
+        {
+            NonForwardingWait {
+                queue: self,
+                condition: Box::new(condition),
+                id: None,
+            }
+        }
+
+        fn wake_one(&self) {
+            self.wakes.fetch_add(1, AtomicOrdering::SeqCst);
+            let waiter = self.waiters.lock().unwrap().1.pop_front();
+            if let Some((_, waker)) = waiter {
+                waker.wake();
+            }
+        }
+
+        fn wake_all(&self) {
+            self.wakes.fetch_add(1, AtomicOrdering::SeqCst);
+            let waiters = std::mem::take(&mut self.waiters.lock().unwrap().1);
+            for (_, waker) in waiters {
+                waker.wake();
+            }
+        }
+
+        fn waiter_count(&self) -> usize {
+            self.waiters.lock().unwrap().1.len()
+        }
+    }
+
     #[test]
     fn test_semaphore_new() {
         let sem = Semaphore::new(100);
@@ -462,19 +1507,19 @@ mod tests {
 
         // Acquire first permit
         let permit1 = sem.try_acquire();
-        assert!(permit1.is_some());
+        assert!(permit1.is_ok());
         assert_eq!(sem.available_permits(), 1);
         assert_eq!(sem.in_use(), 1);
 
         // Acquire second permit
         let permit2 = sem.try_acquire();
-        assert!(permit2.is_some());
+        assert!(permit2.is_ok());
         assert_eq!(sem.available_permits(), 0);
         assert_eq!(sem.in_use(), 2);
 
         // Try to acquire third (should fail)
         let permit3 = sem.try_acquire();
-        assert!(permit3.is_none());
+        assert!(permit3.is_err());
         assert_eq!(sem.available_permits(), 0);
 
         // Release first permit
@@ -484,7 +1529,7 @@ mod tests {
 
         // Can acquire again
         let permit4 = sem.try_acquire();
-        assert!(permit4.is_some());
+        assert!(permit4.is_ok());
         assert_eq!(sem.available_permits(), 0);
     }
 
@@ -504,10 +1549,10 @@ mod tests {
     async fn test_semaphore_acquire_basic() {
         let sem = Semaphore::new(2);
 
-        let permit1 = sem.acquire().await;
+        let permit1 = sem.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 1);
 
-        let permit2 = sem.acquire().await;
+        let permit2 = sem.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 0);
 
         drop(permit1);
@@ -522,13 +1567,13 @@ mod tests {
         let sem = Arc::new(Semaphore::new(1));
 
         // Acquire the only permit
-        let permit1 = sem.acquire().await;
+        let permit1 = sem.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 0);
 
         // Spawn a task that will block waiting for permit
         let sem2 = sem.clone();
         let handle = compio::runtime::spawn(async move {
-            let _permit = sem2.acquire().await;
+            let _permit = sem2.acquire().await.unwrap();
             42
         });
 
@@ -550,14 +1595,14 @@ mod tests {
         let sem = Arc::new(Semaphore::new(1));
 
         // Acquire the only permit
-        let permit = sem.acquire().await;
+        let permit = sem.acquire().await.unwrap();
 
         // Spawn multiple waiting tasks
         let mut handles = Vec::new();
         for i in 0..5 {
             let sem = sem.clone();
             let handle = compio::runtime::spawn(async move {
-                let _permit = sem.acquire().await;
+                let _permit = sem.acquire().await.unwrap();
                 i
             });
             handles.push(handle);
@@ -585,7 +1630,7 @@ mod tests {
         for i in 0..1000 {
             let sem = sem.clone();
             let handle = compio::runtime::spawn(async move {
-                let _permit = sem.acquire().await;
+                let _permit = sem.acquire().await.unwrap();
                 // No need to simulate work - just testing concurrency limit
                 i
             });
@@ -607,10 +1652,10 @@ mod tests {
         let sem = Arc::new(Semaphore::new(10));
         let sem2 = sem.clone();
 
-        let permit1 = sem.acquire().await;
+        let permit1 = sem.acquire().await.unwrap();
         assert_eq!(sem2.available_permits(), 9);
 
-        let permit2 = sem2.acquire().await;
+        let permit2 = sem2.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 8);
 
         drop(permit1);
@@ -618,6 +1663,423 @@ mod tests {
         assert_eq!(sem.available_permits(), 10);
     }
 
+    #[test]
+    fn test_semaphore_try_acquire_owned() {
+        let sem = Arc::new(Semaphore::new(1));
+
+        let permit = sem.clone().try_acquire_owned();
+        assert!(permit.is_ok());
+        assert_eq!(sem.available_permits(), 0);
+        assert!(sem.clone().try_acquire_owned().is_err());
+
+        // Owned permit keeps the semaphore alive
+        assert_eq!(Arc::strong_count(&sem), 2);
+
+        drop(permit);
+        assert_eq!(sem.available_permits(), 1);
+        assert_eq!(Arc::strong_count(&sem), 1);
+    }
+
+    #[test]
+    fn test_semaphore_try_acquire_many() {
+        let sem = Semaphore::new(5);
+
+        let permit = sem.try_acquire_many(3).unwrap();
+        assert_eq!(permit.num_permits(), 3);
+        assert_eq!(sem.available_permits(), 2);
+        assert_eq!(sem.in_use(), 3);
+
+        // All-or-nothing: never takes a partial amount
+        assert!(sem.try_acquire_many(3).is_err());
+        assert_eq!(sem.available_permits(), 2);
+
+        // Zero permits always succeeds and releases nothing
+        let empty = sem.try_acquire_many(0).unwrap();
+        assert_eq!(empty.num_permits(), 0);
+        drop(empty);
+        assert_eq!(sem.available_permits(), 2);
+
+        drop(permit);
+        assert_eq!(sem.available_permits(), 5);
+    }
+
+    /// A queued multi-permit waiter reserves its count so small requests
+    /// cannot keep draining the permits it needs
+    #[compio::test]
+    async fn test_acquire_many_reserves_permits() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let sem = Arc::new(Semaphore::new(2));
+            let small = sem.try_acquire().unwrap();
+
+            let sem_clone = sem.clone();
+            let handle = compio::runtime::spawn(async move {
+                let permit = sem_clone.acquire_many(2).await.unwrap();
+                permit.num_permits()
+            });
+
+            // Let the large request queue up and claim the reservation
+            compio::time::sleep(std::time::Duration::from_millis(10)).await;
+            assert_eq!(sem.inner.reserved.load(Ordering::Acquire), 2);
+
+            // One permit is free, but it is held back for the large request
+            assert_eq!(sem.available_permits(), 1);
+            assert!(sem.try_acquire().is_err());
+
+            drop(small);
+            assert_eq!(handle.await.unwrap(), 2);
+            assert_eq!(sem.inner.reserved.load(Ordering::Acquire), 0);
+            assert_eq!(sem.available_permits(), 2);
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    /// Dropping a queued multi-permit acquire hands its reservation back
+    #[compio::test]
+    async fn test_acquire_many_cancel_releases_reservation() {
+        use std::future::Future;
+
+        let sem = Semaphore::new(3);
+        let held = sem.try_acquire_many(2).unwrap();
+
+        let waker = std::task::Waker::noop();
+        let mut cx = std::task::Context::from_waker(waker);
+        {
+            let mut fut = Box::pin(sem.acquire_many(3));
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(sem.inner.reserved.load(Ordering::Acquire), 3);
+            assert_eq!(sem.inner.many_waiters.load(Ordering::Acquire), 1);
+        }
+
+        assert_eq!(sem.inner.reserved.load(Ordering::Acquire), 0);
+        assert_eq!(sem.inner.many_waiters.load(Ordering::Acquire), 0);
+        assert!(sem.try_acquire().is_ok());
+        drop(held);
+    }
+
+    /// A request larger than the current capacity waits without reserving,
+    /// so it does not block smaller requests, and completes once capacity grows
+    #[compio::test]
+    async fn test_acquire_many_over_max_waits_for_growth() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let sem = Arc::new(Semaphore::new(2));
+
+            let sem_clone = sem.clone();
+            let handle = compio::runtime::spawn(async move {
+                sem_clone.acquire_many(3).await.unwrap().num_permits()
+            });
+
+            compio::time::sleep(std::time::Duration::from_millis(10)).await;
+            assert_eq!(sem.inner.reserved.load(Ordering::Acquire), 0);
+            assert!(sem.try_acquire().is_ok());
+
+            sem.set_max_permits(3);
+            assert_eq!(handle.await.unwrap(), 3);
+            assert_eq!(sem.available_permits(), 3);
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    #[test]
+    fn test_semaphore_close() {
+        let sem = Semaphore::new(2);
+        let permit = sem.try_acquire().unwrap();
+        assert!(!sem.is_closed());
+
+        sem.close();
+        assert!(sem.is_closed());
+        assert_eq!(sem.try_acquire().err(), Some(TryAcquireError::Closed));
+        assert_eq!(sem.try_acquire_many(1).err(), Some(TryAcquireError::Closed));
+
+        // Held permits are still returned to the pool
+        drop(permit);
+        assert_eq!(sem.available_permits(), 2);
+        assert_eq!(sem.try_acquire().err(), Some(TryAcquireError::Closed));
+    }
+
+    #[compio::test]
+    async fn test_semaphore_close_wakes_waiters() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let sem = Arc::new(Semaphore::new(2));
+            let _held = sem.acquire_many(2).await.unwrap();
+
+            let mut handles = Vec::new();
+            for n in [1, 2] {
+                let sem = sem.clone();
+                handles.push(compio::runtime::spawn(async move {
+                    sem.acquire_many(n).await.map(|p| p.num_permits())
+                }));
+            }
+
+            compio::time::sleep(std::time::Duration::from_millis(10)).await;
+            sem.close();
+
+            for handle in handles {
+                assert_eq!(handle.await.unwrap(), Err(AcquireError(())));
+            }
+
+            // The cancelled multi-permit waiter gave its reservation back
+            assert_eq!(sem.inner.reserved.load(Ordering::Acquire), 0);
+            assert_eq!(sem.inner.many_waiters.load(Ordering::Acquire), 0);
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    #[test]
+    fn test_acquire_error_display() {
+        assert_eq!(AcquireError(()).to_string(), "semaphore closed");
+        assert_eq!(TryAcquireError::Closed.to_string(), "semaphore closed");
+        assert_eq!(
+            TryAcquireError::NoPermits.to_string(),
+            "no permits available"
+        );
+    }
+
+    #[test]
+    fn test_permit_forget() {
+        let sem = Semaphore::new(3);
+
+        sem.try_acquire_many(2).unwrap().forget();
+        assert_eq!(sem.available_permits(), 1);
+
+        let sem = Arc::new(Semaphore::new(3));
+        sem.clone().try_acquire_owned().unwrap().forget();
+        assert_eq!(sem.available_permits(), 2);
+    }
+
+    #[test]
+    fn test_permit_split_and_merge() {
+        let sem = Semaphore::new(5);
+        let mut permit = sem.try_acquire_many(4).unwrap();
+
+        // Splitting more than held leaves the permit untouched
+        assert!(permit.split(5).is_none());
+        assert_eq!(permit.num_permits(), 4);
+
+        let child = permit.split(3).unwrap();
+        assert_eq!(permit.num_permits(), 1);
+        assert_eq!(child.num_permits(), 3);
+        assert_eq!(sem.available_permits(), 1);
+
+        permit.merge(child);
+        assert_eq!(permit.num_permits(), 4);
+        assert_eq!(sem.available_permits(), 1);
+
+        drop(permit);
+        assert_eq!(sem.available_permits(), 5);
+    }
+
+    #[test]
+    fn test_owned_permit_split_and_merge() {
+        let sem = Arc::new(Semaphore::new(5));
+        let mut permit = sem.clone().try_acquire_many_owned(4).unwrap();
+
+        let child = permit.split(2).unwrap();
+        assert!(Arc::ptr_eq(child.semaphore(), &sem));
+        drop(child);
+        assert_eq!(sem.available_permits(), 3);
+
+        permit.merge(sem.clone().try_acquire_owned().unwrap());
+        assert_eq!(permit.num_permits(), 3);
+        drop(permit);
+        assert_eq!(sem.available_permits(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot merge permits from different semaphores")]
+    fn test_permit_merge_different_semaphores_panics() {
+        let sem1 = Semaphore::new(1);
+        let sem2 = Semaphore::new(1);
+        let mut permit = sem1.try_acquire().unwrap();
+        permit.merge(sem2.try_acquire().unwrap());
+    }
+
+    #[compio::test]
+    async fn test_semaphore_acquire_timeout() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let sem = Arc::new(Semaphore::new(1));
+
+            // Available permit: acquired immediately
+            let permit = sem
+                .acquire_timeout(Duration::from_millis(10))
+                .await
+                .unwrap();
+
+            let result = sem.acquire_timeout(Duration::from_millis(20)).await;
+            assert_eq!(result.err(), Some(AcquireTimeout::Elapsed { queued: true }));
+
+            // Deadline already passed: never queued
+            let result = sem.acquire_until(Instant::now()).await;
+            assert_eq!(
+                result.err(),
+                Some(AcquireTimeout::Elapsed { queued: false })
+            );
+
+            // Released while waiting: acquired before the deadline
+            let sem_clone = sem.clone();
+            let handle = compio::runtime::spawn(async move {
+                sem_clone
+                    .acquire_timeout(Duration::from_secs(1))
+                    .await
+                    .map(|p| p.num_permits())
+            });
+            compio::time::sleep(Duration::from_millis(10)).await;
+            drop(permit);
+            assert_eq!(handle.await.unwrap(), Ok(1));
+
+            sem.close();
+            let result = sem.acquire_timeout(Duration::from_millis(10)).await;
+            assert_eq!(result.err(), Some(AcquireTimeout::Closed));
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    /// Expired timed acquires must not leave wakers behind in the queue
+    #[cfg(not(target_os = "linux"))]
+    #[compio::test]
+    async fn test_semaphore_acquire_timeout_deregisters() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let sem = Arc::new(Semaphore::new(1));
+            let _held = sem.acquire().await.unwrap();
+
+            let mut handles = Vec::new();
+            for _ in 0..3 {
+                let sem = sem.clone();
+                handles.push(compio::runtime::spawn(async move {
+                    sem.acquire_timeout(Duration::from_millis(20))
+                        .await
+                        .is_err()
+                }));
+            }
+            for handle in handles {
+                assert!(handle.await.unwrap());
+            }
+
+            assert_eq!(sem.inner.waiters.waiter_count(), 0);
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    #[test]
+    fn test_set_max_permits_grow_and_shrink() {
+        let sem = Semaphore::new(4);
+        let held = sem.try_acquire_many(3).unwrap();
+
+        sem.set_max_permits(6);
+        assert_eq!(sem.max_permits(), 6);
+        assert_eq!(sem.available_permits(), 3);
+        assert_eq!(sem.in_use(), 3);
+
+        // Shrink below the held count: free permits go now, the rest lazily
+        sem.set_max_permits(1);
+        assert_eq!(sem.max_permits(), 1);
+        assert_eq!(sem.available_permits(), 0);
+        assert_eq!(sem.in_use(), 3);
+        assert_eq!(sem.inner.pending_shrink.load(Ordering::Acquire), 2);
+
+        drop(held);
+        assert_eq!(sem.available_permits(), 1);
+        assert_eq!(sem.in_use(), 0);
+        assert_eq!(sem.inner.pending_shrink.load(Ordering::Acquire), 0);
+    }
+
+    /// Shrinking below a held reservation must not block smaller acquires
+    #[compio::test]
+    async fn test_shrink_below_reservation_does_not_block_small_acquires() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let sem = Arc::new(Semaphore::new(4));
+            let held = sem.try_acquire_many(3).unwrap();
+
+            // Queues and reserves 4
+            let large = {
+                let sem = sem.clone();
+                compio::runtime::spawn(async move { sem.acquire_many(4).await.map(|_| ()) })
+            };
+            compio::time::sleep(Duration::from_millis(10)).await;
+            assert_eq!(sem.inner.reserved.load(Ordering::Acquire), 4);
+
+            sem.set_max_permits(2);
+            drop(held);
+            assert_eq!(sem.max_permits(), 2);
+            assert_eq!(sem.available_permits(), 2);
+            assert_eq!(sem.in_use(), 0);
+
+            // The unsatisfiable reservation no longer holds anyone back
+            assert!(sem.try_acquire().is_ok());
+            compio::time::timeout(Duration::from_millis(500), sem.acquire())
+                .await
+                .expect("small acquire blocked by an oversized reservation")
+                .unwrap();
+
+            // The large request still waits for capacity to grow back
+            sem.set_max_permits(4);
+            compio::time::timeout(Duration::from_millis(500), large)
+                .await
+                .expect("large acquire should proceed after growth")
+                .unwrap()
+                .unwrap();
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    #[test]
+    fn test_grow_cancels_pending_shrink() {
+        let sem = Semaphore::new(4);
+        let held = sem.try_acquire_many(4).unwrap();
+
+        sem.set_max_permits(2);
+        assert_eq!(sem.inner.pending_shrink.load(Ordering::Acquire), 2);
+
+        // Growing back first cancels what was still owed
+        sem.add_permits(1);
+        assert_eq!(sem.max_permits(), 3);
+        assert_eq!(sem.available_permits(), 0);
+        assert_eq!(sem.inner.pending_shrink.load(Ordering::Acquire), 1);
+
+        drop(held);
+        assert_eq!(sem.available_permits(), 3);
+        assert_eq!(sem.in_use(), 0);
+    }
+
+    /// add_permits/reduce_permits keep in_use() consistent (no underflow)
+    #[test]
+    fn test_add_and_reduce_permits_track_max() {
+        let sem = Semaphore::new(2);
+        sem.add_permits(3);
+        assert_eq!(sem.max_permits(), 5);
+        assert_eq!(sem.in_use(), 0);
+
+        let permit = sem.try_acquire_many(5).unwrap();
+        assert_eq!(sem.in_use(), 5);
+        drop(permit);
+
+        assert_eq!(sem.reduce_permits(4), 4);
+        assert_eq!(sem.max_permits(), 1);
+        assert_eq!(sem.in_use(), 0);
+    }
+
+    #[test]
+    fn test_forget_shrinks_capacity() {
+        let sem = Semaphore::new(4);
+        let mut permit = sem.try_acquire_many(4).unwrap();
+
+        // Forgetting first covers an outstanding shrink
+        sem.set_max_permits(3);
+        permit.split(2).unwrap().forget();
+        assert_eq!(sem.max_permits(), 2);
+        assert_eq!(sem.inner.pending_shrink.load(Ordering::Acquire), 0);
+        assert_eq!(sem.in_use(), 2);
+
+        drop(permit);
+        assert_eq!(sem.available_permits(), 2);
+        assert_eq!(sem.in_use(), 0);
+    }
+
     #[test]
     #[should_panic(expected = "Semaphore must have at least one permit")]
     fn test_semaphore_zero_permits_panics() {
@@ -641,7 +2103,7 @@ mod tests {
             let released = Arc::new(AtomicBool::new(false));
 
             // Take the permit (permits = 0)
-            let _permit = sem.acquire().await;
+            let _permit = sem.acquire().await.unwrap();
 
             // Set up the mock to inject permit release in race window
             let sem_clone = sem.clone();
@@ -689,7 +2151,7 @@ mod tests {
             let sem = Arc::new(SemaphoreGeneric::<MockWaiterQueue>::new(1));
 
             // Take the permit (permits = 0)
-            let _permit = sem.acquire().await;
+            let _permit = sem.acquire().await.unwrap();
 
             // Set up mock to release MULTIPLE permits during registration
             let sem_clone = sem.clone();
@@ -725,7 +2187,7 @@ mod tests {
             let sem = Arc::new(SemaphoreGeneric::<MockWaiterQueue>::new(1));
 
             // Take the permit (permits = 0)
-            let _permit = sem.acquire().await;
+            let _permit = sem.acquire().await.unwrap();
 
             // Set up mock to release permit AND explicitly wake during registration
             let sem_clone = sem.clone();
@@ -759,7 +2221,7 @@ mod tests {
             let sem = Arc::new(SemaphoreGeneric::<MockWaiterQueue>::new(1));
 
             // Take the permit (permits = 0)
-            let _permit = sem.acquire().await;
+            let _permit = sem.acquire().await.unwrap();
 
             // Set up mock to release permit then immediately steal it back
             let sem_clone = sem.clone();
@@ -793,6 +2255,88 @@ mod tests {
         .expect("Test timed out");
     }
 
+    /// Test close() during registration is caught by the condition re-check
+    ///
+    /// Without `is_closed()` in the add_waiter_if condition, the waiter would
+    /// park after close() had already drained the queue and never wake.
+    #[compio::test]
+    async fn test_mock_close_during_registration() {
+        compio::time::timeout(std::time::Duration::from_secs(2), async {
+            let sem = Arc::new(SemaphoreGeneric::<MockWaiterQueue>::new(1));
+
+            // Take the permit (permits = 0)
+            let _permit = sem.acquire().await.unwrap();
+
+            // Set up mock to close the semaphore in the race window
+            let sem_clone = sem.clone();
+            sem.inner.waiters.set_on_add_waiter(move || {
+                sem_clone.close();
+            });
+
+            let result =
+                compio::time::timeout(std::time::Duration::from_millis(500), sem.acquire())
+                    .await
+                    .expect("Should not timeout - re-check should observe close");
+
+            assert_eq!(result.err(), Some(AcquireError(())));
+        })
+        .await
+        .expect("Test timed out");
+    }
+
+    /// A woken acquire dropped before it polls hands its wake to the next waiter
+    ///
+    /// The queue does not forward wakes itself, so without the semaphore
+    /// re-issuing it the second waiter sleeps next to a free permit.
+    #[test]
+    fn test_woken_acquire_dropped_before_poll_forwards_wake() {
+        let sem = SemaphoreGeneric::<NonForwardingQueue>::new(1);
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let permit = sem.try_acquire().unwrap();
+        let mut first = Box::pin(sem.acquire());
+        let mut second = Box::pin(sem.acquire());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(sem.inner.waiters.waiter_count(), 2);
+
+        // wake_one() picks `first`, which is then cancelled without polling
+        drop(permit);
+        drop(first);
+        assert_eq!(
+            sem.inner.waiters.waiter_count(),
+            0,
+            "wake swallowed by the dropped acquire was not forwarded"
+        );
+
+        match second.as_mut().poll(&mut cx) {
+            std::task::Poll::Ready(Ok(permit)) => drop(permit),
+            other => panic!("second waiter did not acquire: {:?}", other.is_ready()),
+        }
+        assert_eq!(sem.available_permits(), 1);
+    }
+
+    /// Cancelling a waiter that was never woken does not issue a wake
+    #[test]
+    fn test_unwoken_acquire_dropped_does_not_wake() {
+        let sem = SemaphoreGeneric::<NonForwardingQueue>::new(1);
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let permit = sem.try_acquire().unwrap();
+        let mut first = Box::pin(sem.acquire());
+        let mut second = Box::pin(sem.acquire());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+
+        // `first` is woken and the permit is free when `second` is cancelled
+        drop(permit);
+        assert_eq!(sem.inner.waiters.wakes.load(AtomicOrdering::SeqCst), 1);
+        drop(second);
+        assert_eq!(sem.inner.waiters.wakes.load(AtomicOrdering::SeqCst), 1);
+
+        assert!(first.as_mut().poll(&mut cx).is_ready());
+    }
+
     /// Sanity check that MockWaiterQueue works correctly for normal operations
     ///
     /// This verifies the mock properly delegates to the real implementation
@@ -803,16 +2347,16 @@ mod tests {
             let sem = Arc::new(SemaphoreGeneric::<MockWaiterQueue>::new(3));
 
             // Normal acquire/release without any hooks
-            let permit1 = sem.acquire().await;
+            let permit1 = sem.acquire().await.unwrap();
             assert_eq!(sem.available_permits(), 2);
 
-            let permit2 = sem.acquire().await;
+            let permit2 = sem.acquire().await.unwrap();
             assert_eq!(sem.available_permits(), 1);
 
             drop(permit1);
             assert_eq!(sem.available_permits(), 2);
 
-            let permit3 = sem.acquire().await;
+            let permit3 = sem.acquire().await.unwrap();
             assert_eq!(sem.available_permits(), 1);
 
             drop(permit2);
@@ -821,7 +2365,7 @@ mod tests {
 
             // Verify try_acquire works
             let permit = sem.try_acquire();
-            assert!(permit.is_some());
+            assert!(permit.is_ok());
             assert_eq!(sem.available_permits(), 2);
         })
         .await
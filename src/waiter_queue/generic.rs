@@ -2,7 +2,7 @@
 //!
 //! **Phase 1 Implementation**: Lock-free single-waiter optimization + parking_lot for multi-waiter:
 //! - Single-waiter fast path: AtomicWaker (lock-free atomic operations!)
-//! - Multi-waiter slow path: parking_lot::Mutex + VecDeque (2-5x faster than std::Mutex)
+//! - Multi-waiter slow path: parking_lot::Mutex + intrusive waiter list (2-5x faster than std::Mutex)
 //! - Atomic mode state machine: Empty → Single → Multi
 //!
 //! **Future Phases**: Phase 2 will add platform-specific optimizations:
@@ -13,10 +13,13 @@
 //! Performance characteristics:
 //! - Single waiter (common case): Lock-free atomic operations (~nanoseconds, zero mutex overhead)
 //! - Multiple waiters: Fast parking_lot mutex (2-5x faster than std::Mutex)
+//! - Cancelling a waiter: O(1) unlink, no allocation per waiter
 //! - No kernel involvement except waker.wake() which goes to the runtime
 
-use std::collections::VecDeque;
-use std::sync::atomic::{AtomicU8, Ordering};
+use std::cell::UnsafeCell;
+use std::marker::PhantomPinned;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU8, Ordering};
 use std::task::Waker;
 
 use super::WaiterQueueTrait;
@@ -37,15 +40,135 @@ enum Mode {
     Empty = 0,
     /// Exactly one waiter (uses AtomicWaker, lock-free!)
     Single = 1,
-    /// Multiple waiters (uses Mutex<VecDeque>)
+    /// Multiple waiters (uses Mutex<WaiterList>)
     Multi = 2,
 }
 
+/// A registered waiter, stored inside its (pinned) `add_waiter_if` future
+///
+/// All fields are only accessed with the `multi` lock held.
+struct Node {
+    /// Waker to use when this node is popped
+    waker: Option<Waker>,
+    prev: *mut Node,
+    next: *mut Node,
+    /// Whether the node is linked into the list
+    queued: bool,
+}
+
+impl Node {
+    fn new() -> Self {
+        Self {
+            waker: None,
+            prev: ptr::null_mut(),
+            next: ptr::null_mut(),
+            queued: false,
+        }
+    }
+}
+
+/// Intrusive FIFO list of waiter nodes
+///
+/// Nodes live in their futures, so registering does not allocate and a
+/// cancelled future unlinks itself in O(1).
+struct WaiterList {
+    head: *mut Node,
+    tail: *mut Node,
+    len: usize,
+}
+
+// SAFETY: the list is only reachable through the `multi` mutex, and every node
+// stays valid while it is linked (futures unlink themselves before going away)
+unsafe impl Send for WaiterList {}
+
+impl WaiterList {
+    const fn new() -> Self {
+        Self {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Link a node at the back
+    ///
+    /// # Safety
+    ///
+    /// `node` must be valid, not already queued, and must stay at the same
+    /// address until it is removed.
+    unsafe fn push_back(&mut self, node: *mut Node) {
+        // SAFETY: guaranteed by the caller; `tail` is a live queued node
+        unsafe {
+            (*node).prev = self.tail;
+            (*node).next = ptr::null_mut();
+            (*node).queued = true;
+            if self.tail.is_null() {
+                self.head = node;
+            } else {
+                (*self.tail).next = node;
+            }
+        }
+        self.tail = node;
+        self.len += 1;
+    }
+
+    /// Unlink a node; returns false if it was not queued
+    ///
+    /// # Safety
+    ///
+    /// `node` must be valid, and if queued, queued in this list.
+    unsafe fn remove(&mut self, node: *mut Node) -> bool {
+        // SAFETY: guaranteed by the caller; neighbours are live queued nodes
+        unsafe {
+            if !(*node).queued {
+                return false;
+            }
+            let (prev, next) = ((*node).prev, (*node).next);
+            if prev.is_null() {
+                self.head = next;
+            } else {
+                (*prev).next = next;
+            }
+            if next.is_null() {
+                self.tail = prev;
+            } else {
+                (*next).prev = prev;
+            }
+            (*node).prev = ptr::null_mut();
+            (*node).next = ptr::null_mut();
+            (*node).queued = false;
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Unlink the oldest node and take its waker
+    fn pop_front(&mut self) -> Option<Waker> {
+        let node = self.head;
+        if node.is_null() {
+            return None;
+        }
+        // SAFETY: `head` is a live node queued in this list
+        unsafe {
+            self.remove(node);
+            (*node).waker.take()
+        }
+    }
+}
+
 /// Generic waiter queue implementation (Phase 1)
 ///
 /// Uses a hybrid approach:
 /// - Single waiter fast path: AtomicWaker (lock-free!)
-/// - Multiple waiters slow path: parking_lot::Mutex + VecDeque
+/// - Multiple waiters slow path: parking_lot::Mutex + intrusive waiter list
 ///
 /// This provides optimal performance for the common case (single waiter)
 /// while still handling high contention gracefully.
@@ -57,8 +180,16 @@ pub struct WaiterQueue {
     /// AtomicWaker uses pure atomic operations, no mutex needed
     single: AtomicWaker,
 
+    /// Node of the waiter owning the single slot (null if none)
+    ///
+    /// This is the ownership token for `single`: whoever swaps it out owns the
+    /// registered waker, so a waiter that was already woken can never take a
+    /// later waiter's waker. It also lets the slot be migrated into the multi
+    /// list.
+    single_node: AtomicPtr<Node>,
+
     /// Slow path: multiple waiters
-    multi: Mutex<VecDeque<Waker>>,
+    multi: Mutex<WaiterList>,
 }
 
 impl WaiterQueue {
@@ -67,7 +198,8 @@ impl WaiterQueue {
         Self {
             mode: AtomicU8::new(Mode::Empty.into()),
             single: AtomicWaker::new(),
-            multi: Mutex::new(VecDeque::new()),
+            single_node: AtomicPtr::new(ptr::null_mut()),
+            multi: Mutex::new(WaiterList::new()),
         }
     }
 
@@ -108,6 +240,8 @@ impl WaiterQueue {
     /// - Re-checks after registration to prevent lost wakeups
     ///
     /// Returns a future that completes when condition is true or waiter is woken.
+    /// Dropping the future while registered unlinks it; if it had already been
+    /// woken, the wake is passed on to the next waiter.
     pub fn add_waiter_if<'a, F>(
         &'a self,
         condition: F,
@@ -118,8 +252,8 @@ impl WaiterQueue {
         // Track where we registered for proper cleanup on drop
         enum RegistrationState {
             None,   // Not yet registered
-            Single, // Registered in single slot
-            Multi,  // Registered in multi queue
+            Single, // Registered in single slot (may migrate to multi)
+            Multi,  // Node linked into the multi list
         }
 
         // Use a struct to track registration state across polls
@@ -127,30 +261,30 @@ impl WaiterQueue {
             queue: &'a WaiterQueue,
             condition: F,
             state: RegistrationState,
+            /// Our entry in the multi list; only touched under the `multi` lock
+            node: UnsafeCell<Node>,
+            /// The list points into `node`, so we must not move once registered
+            _pin: PhantomPinned,
         }
 
+        // SAFETY: the node (and the neighbour pointers inside it) is only
+        // accessed with the `multi` lock held, so the future may move to and be
+        // polled or dropped on another thread like the `VecDeque`-based one could
+        unsafe impl<'a, F: Send> Send for AddWaiterFuture<'a, F> {}
+
         impl<'a, F> Drop for AddWaiterFuture<'a, F> {
             fn drop(&mut self) {
-                // Deregister if we're still pending
-                match self.state {
-                    RegistrationState::Single => {
-                        // Try to clean up single slot
-                        // If we successfully take it, reset to Empty
-                        if self.queue.single.take().is_some() {
-                            self.queue.store_mode(Mode::Empty, Ordering::Release);
-                        }
-                    }
-                    RegistrationState::Multi => {
-                        // Can't efficiently remove from VecDeque without knowing our position
-                        // The waker will be a no-op if called (future already dropped)
-                        // This is acceptable - spurious wake is safe, just slightly inefficient
-                        //
-                        // Note: We could track position in VecDeque but that adds significant
-                        // complexity. The parking_lot Mutex is fast enough that this is okay.
-                    }
-                    RegistrationState::None => {
-                        // Not registered, nothing to clean up
-                    }
+                let single = match self.state {
+                    RegistrationState::None => return, // Not registered, nothing to clean up
+                    RegistrationState::Single => true,
+                    RegistrationState::Multi => false,
+                };
+                // SAFETY: `node` is ours and registered as `state` says
+                let woken = unsafe { self.queue.deregister(self.node.get(), single) };
+                if woken {
+                    // We took a wake_one() meant for a live waiter (e.g. an
+                    // acquire cancelled by a timeout): hand it on
+                    self.queue.wake_one();
                 }
             }
         }
@@ -169,12 +303,22 @@ impl WaiterQueue {
 
                 // SAFETY: We don't move out of self, just access fields
                 let this = unsafe { self.as_mut().get_unchecked_mut() };
+                let node = this.node.get();
 
-                // If already registered, just wait for wake
-                if !matches!(this.state, RegistrationState::None) {
-                    // We were woken - complete the future
-                    this.state = RegistrationState::None;
-                    return Poll::Ready(());
+                // If already registered, we were woken - complete the future. If
+                // this poll was spurious our entry is still there, so drop it.
+                match std::mem::replace(&mut this.state, RegistrationState::None) {
+                    RegistrationState::None => {}
+                    RegistrationState::Single => {
+                        // SAFETY: `node` is ours and registered in the single slot
+                        unsafe { this.queue.deregister(node, true) };
+                        return Poll::Ready(());
+                    }
+                    RegistrationState::Multi => {
+                        // SAFETY: `node` is ours and registered in the multi list
+                        unsafe { this.queue.deregister(node, false) };
+                        return Poll::Ready(());
+                    }
                 }
 
                 let queue = this.queue;
@@ -198,18 +342,23 @@ impl WaiterQueue {
 
                         // Check before registration
                         if condition() {
-                            queue.store_mode(Mode::Empty, Ordering::Release);
+                            // A waiter may have moved the mode to Multi meanwhile
+                            queue.release_single_mode();
                             return Poll::Ready(());
                         }
 
                         // Register with AtomicWaker (lock-free atomic operation!)
                         queue.single.register(cx.waker());
 
+                        // Publish ownership; whoever takes it also finds the waker
+                        queue.single_node.store(node, Ordering::SeqCst);
+
                         // Re-check after registration to prevent lost wake
                         if condition() {
-                            // Take the waker back and reset mode
-                            queue.single.take();
-                            queue.store_mode(Mode::Empty, Ordering::Release);
+                            // Take our registration back (it may have been
+                            // migrated or woken meanwhile)
+                            // SAFETY: `node` is ours and registered in the single slot
+                            unsafe { queue.deregister(node, true) };
                             return Poll::Ready(());
                         }
 
@@ -228,17 +377,30 @@ impl WaiterQueue {
                 let mut waiters = queue.multi.lock();
 
                 // Migrate single-slot waiter if present (atomically take it)
-                if let Some(prev) = queue.single.take() {
-                    waiters.push_back(prev);
+                let prev_node = queue.single_node.swap(ptr::null_mut(), Ordering::SeqCst);
+                if !prev_node.is_null() {
+                    // SAFETY: the single waiter can only deregister once it finds
+                    // the slot taken, which makes it wait for this lock; its node
+                    // is pinned until then
+                    unsafe {
+                        (*prev_node).waker = queue.single.take();
+                        waiters.push_back(prev_node);
+                    }
                 }
 
                 // Register this waiter
-                waiters.push_back(cx.waker().clone());
+                // SAFETY: we hold the lock; `node` is pinned inside this future,
+                // which unlinks it before completing or being dropped
+                unsafe {
+                    (*node).waker = Some(cx.waker().clone());
+                    waiters.push_back(node);
+                }
 
                 // Re-check after registration to prevent lost wake
                 if condition() {
                     // Remove our own registration
-                    let _ = waiters.pop_back();
+                    // SAFETY: linked just above, under the same lock
+                    unsafe { waiters.remove(node) };
                     // If nothing remains, update mode accordingly
                     if waiters.is_empty() {
                         queue.store_mode(Mode::Empty, Ordering::Release);
@@ -258,6 +420,8 @@ impl WaiterQueue {
             queue: self,
             condition,
             state: RegistrationState::None,
+            node: UnsafeCell::new(Node::new()),
+            _pin: PhantomPinned,
         }
     }
 
@@ -271,47 +435,75 @@ impl WaiterQueue {
             }
             Mode::Single => {
                 // Lock-free atomic wake using AtomicWaker!
-                if let Some(w) = self.single.take() {
+                if let Some(w) = self.take_single() {
                     // Check if multi has waiters to decide next mode
-                    let has_multi = { !self.multi.lock().is_empty() };
-                    self.store_mode(
-                        if has_multi { Mode::Multi } else { Mode::Empty },
-                        Ordering::Release,
-                    );
+                    self.update_mode_from_multi();
                     w.wake();
                 } else {
-                    // Nothing in single (registration race) → try multi, then fix mode
-                    if !self.wake_one_from_multi() {
-                        // Both empty, check and update mode appropriately
-                        let has_multi = { !self.multi.lock().is_empty() };
-                        self.store_mode(
-                            if has_multi { Mode::Multi } else { Mode::Empty },
-                            Ordering::Release,
-                        );
-                    }
+                    // Nothing in single: the owner is still registering (and
+                    // re-checks its condition) or just left (and resets the
+                    // mode itself), so only try multi
+                    self.wake_one_from_multi();
                 }
             }
             Mode::Multi => {
                 // Prefer multi; if empty, try single and update mode accordingly
                 if !self.wake_one_from_multi() {
                     // Try single waiter (lock-free!)
-                    if let Some(w) = self.single.take() {
+                    if let Some(w) = self.take_single() {
                         // Check if multi still has waiters for next mode
-                        let has_multi = { !self.multi.lock().is_empty() };
-                        self.store_mode(
-                            if has_multi { Mode::Multi } else { Mode::Empty },
-                            Ordering::Release,
-                        );
+                        self.update_mode_from_multi();
                         w.wake();
                     } else {
                         // Both empty, reset mode
-                        self.store_mode(Mode::Empty, Ordering::Release);
+                        self.update_mode_from_multi();
                     }
                 }
             }
         }
     }
 
+    /// Set the mode to Multi or Empty depending on the multi list
+    ///
+    /// Done under the lock so a waiter registering concurrently (which sets
+    /// Multi under the same lock) cannot be overwritten with Empty.
+    fn update_mode_from_multi(&self) {
+        let waiters = self.multi.lock();
+        self.store_mode(
+            if waiters.is_empty() {
+                Mode::Empty
+            } else {
+                Mode::Multi
+            },
+            Ordering::Release,
+        );
+    }
+
+    /// Leave Single mode after giving up the single slot
+    ///
+    /// Only resets a Single mode: if a waiter switched to Multi meanwhile, the
+    /// multi list holds waiters and the mode must stay.
+    fn release_single_mode(&self) {
+        let _ = self.compare_exchange_mode(
+            Mode::Single,
+            Mode::Empty,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+    }
+
+    /// Take the single-slot registration, if any (internal helper)
+    fn take_single(&self) -> Option<Waker> {
+        if self
+            .single_node
+            .swap(ptr::null_mut(), Ordering::SeqCst)
+            .is_null()
+        {
+            return None;
+        }
+        self.single.take()
+    }
+
     /// Wake one waiter from multi queue (internal helper)
     /// Returns true if a waiter was woken, false otherwise
     fn wake_one_from_multi(&self) -> bool {
@@ -330,21 +522,65 @@ impl WaiterQueue {
         false
     }
 
+    /// Withdraw a registration; returns true if it had already been woken
+    ///
+    /// A registration that is no longer in the single slot or the multi list
+    /// was taken by `wake_one()` or `wake_all()`.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to the calling future, which registered it in the
+    /// single slot (`single`) or the multi list.
+    unsafe fn deregister(&self, node: *mut Node, single: bool) -> bool {
+        if single
+            && self
+                .single_node
+                .compare_exchange(node, ptr::null_mut(), Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+        {
+            // Still in the single slot: nobody else can see us
+            self.single.take();
+            self.release_single_mode();
+            return false;
+        }
+
+        // Not in the single slot: another waiter may have migrated us into the
+        // multi list, which it does while holding this lock
+        let mut waiters = self.multi.lock();
+        // SAFETY: `node` is ours; if queued, it is queued in this list
+        if !unsafe { waiters.remove(node) } {
+            return true;
+        }
+        if waiters.is_empty() {
+            // Nothing left to wake; a concurrent registration may already have
+            // moved on, so only reset the mode if it is still Multi
+            let _ = self.compare_exchange_mode(
+                Mode::Multi,
+                Mode::Empty,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            );
+        }
+        false
+    }
+
     /// Wake all waiting tasks
     pub fn wake_all(&self) {
         // Drain both storages
         // Single: lock-free atomic take
-        let single_waker = self.single.take();
+        let single_waker = self.take_single();
 
-        // Multi: lock and drain
+        // Multi: lock and drain, resetting the mode under the same lock
         let multi_wakers = {
             let mut waiters = self.multi.lock();
-            std::mem::take(&mut *waiters)
+            let mut wakers = Vec::with_capacity(waiters.len());
+            while let Some(waker) = waiters.pop_front() {
+                wakers.push(waker);
+            }
+            self.store_mode(Mode::Empty, Ordering::Release);
+            wakers
         };
 
-        // Reset mode after draining
-        self.store_mode(Mode::Empty, Ordering::Release);
-
         // Wake all outside lock
         if let Some(waker) = single_waker {
             waker.wake();
@@ -355,6 +591,14 @@ impl WaiterQueue {
         }
     }
 
+    /// Whether a dropped, already-woken waiter forwards its wake
+    ///
+    /// Always true: a woken future dropped before completing calls
+    /// `wake_one()` from its `Drop`.
+    pub fn forwards_cancelled_wakes(&self) -> bool {
+        true
+    }
+
     /// Get the number of waiting tasks (for debugging/stats)
     ///
     /// Note: This provides a best-effort count that may be slightly
@@ -407,6 +651,10 @@ impl WaiterQueueTrait for WaiterQueue {
         WaiterQueue::wake_all(self)
     }
 
+    fn forwards_cancelled_wakes(&self) -> bool {
+        WaiterQueue::forwards_cancelled_wakes(self)
+    }
+
     fn waiter_count(&self) -> usize {
         WaiterQueue::waiter_count(self)
     }
@@ -483,6 +731,15 @@ mod tests {
         );
     }
 
+    /// The intrusive node must not make the future `!Send`
+    #[test]
+    fn test_add_waiter_future_is_send() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let queue = WaiterQueue::new();
+        assert_send(&queue.add_waiter_if(|| false));
+    }
+
     #[compio::test]
     async fn test_condition_check() {
         let queue = WaiterQueue::new();
@@ -500,6 +757,112 @@ mod tests {
         assert_eq!(queue.waiter_count(), 0);
     }
 
+    /// A dropped multi-mode registration must remove its waker from the queue
+    #[test]
+    fn test_multi_drop_deregisters_waiter() {
+        use std::future::Future;
+
+        let queue = WaiterQueue::new();
+        let waker = std::task::Waker::noop();
+        let mut cx = std::task::Context::from_waker(waker);
+
+        // First waiter claims the single slot, second migrates both to multi
+        let mut first = Box::pin(queue.add_waiter_if(|| false));
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        let mut second = Box::pin(queue.add_waiter_if(|| false));
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(queue.load_mode(Ordering::Relaxed), Mode::Multi);
+        assert_eq!(queue.multi.lock().len(), 2);
+
+        drop(second);
+        assert_eq!(queue.multi.lock().len(), 1);
+
+        drop(first);
+        assert_eq!(queue.multi.lock().len(), 0);
+        assert_eq!(queue.waiter_count(), 0);
+    }
+
+    /// Counts how often it was woken
+    struct CountingWaker(std::sync::atomic::AtomicUsize);
+
+    impl std::task::Wake for CountingWaker {
+        fn wake(self: std::sync::Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn counting_waker() -> (std::sync::Arc<CountingWaker>, Waker) {
+        let counter = std::sync::Arc::new(CountingWaker(Default::default()));
+        (counter.clone(), Waker::from(counter))
+    }
+
+    /// A waiter woken but dropped before polling passes the wake on
+    #[test]
+    fn test_woken_drop_forwards_wake() {
+        use std::future::Future;
+
+        let queue = WaiterQueue::new();
+        // One closure type, so the futures can share an array
+        let never = || false;
+        let (first_count, first_waker) = counting_waker();
+        let (second_count, second_waker) = counting_waker();
+        let (third_count, third_waker) = counting_waker();
+
+        let mut first = Box::pin(queue.add_waiter_if(never));
+        let mut second = Box::pin(queue.add_waiter_if(never));
+        let mut third = Box::pin(queue.add_waiter_if(never));
+        for (fut, waker) in [
+            (first.as_mut(), &first_waker),
+            (second.as_mut(), &second_waker),
+            (third.as_mut(), &third_waker),
+        ] {
+            let mut cx = std::task::Context::from_waker(waker);
+            assert!(fut.poll(&mut cx).is_pending());
+        }
+
+        // A cancelled waiter in the middle is unlinked and never woken
+        drop(second);
+        assert_eq!(queue.multi.lock().len(), 2);
+
+        queue.wake_one();
+        assert_eq!(first_count.0.load(Ordering::SeqCst), 1);
+        assert_eq!(third_count.0.load(Ordering::SeqCst), 0);
+
+        // Cancelled after being woken (e.g. lost a select): third gets the wake
+        drop(first);
+        assert_eq!(third_count.0.load(Ordering::SeqCst), 1);
+        assert_eq!(second_count.0.load(Ordering::SeqCst), 0);
+
+        let mut cx = std::task::Context::from_waker(&third_waker);
+        assert!(third.as_mut().poll(&mut cx).is_ready());
+        assert_eq!(queue.multi.lock().len(), 0);
+    }
+
+    /// The single-slot waiter forwards a wake it received too
+    #[test]
+    fn test_single_woken_drop_forwards_wake() {
+        use std::future::Future;
+
+        let queue = WaiterQueue::new();
+        let (_, first_waker) = counting_waker();
+        let (second_count, second_waker) = counting_waker();
+
+        let mut first = Box::pin(queue.add_waiter_if(|| false));
+        let mut cx = std::task::Context::from_waker(&first_waker);
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(queue.load_mode(Ordering::Relaxed), Mode::Single);
+
+        queue.wake_one();
+
+        // Registers after the wake, so it is not the one that was woken
+        let mut second = Box::pin(queue.add_waiter_if(|| false));
+        let mut cx = std::task::Context::from_waker(&second_waker);
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+
+        drop(first);
+        assert_eq!(second_count.0.load(Ordering::SeqCst), 1);
+    }
+
     // Note: Waker-specific tests removed since poll_add_waiter_if now gets
     // the waker from Context. Functionality is tested at higher levels
     // (Condvar/Semaphore tests).
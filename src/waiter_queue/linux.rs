@@ -96,6 +96,17 @@ impl WaiterQueue {
         }
     }
 
+    /// Whether a dropped, already-woken waiter forwards its wake
+    ///
+    /// A completed futex wait cannot be handed on, so only the generic
+    /// fallback does this.
+    pub fn forwards_cancelled_wakes(&self) -> bool {
+        match self {
+            WaiterQueue::IoUring(_) => false,
+            WaiterQueue::Generic(q) => q.forwards_cancelled_wakes(),
+        }
+    }
+
     /// Get the number of waiting tasks
     pub fn waiter_count(&self) -> usize {
         match self {
@@ -131,6 +142,10 @@ impl super::WaiterQueueTrait for WaiterQueue {
         WaiterQueue::wake_all(self)
     }
 
+    fn forwards_cancelled_wakes(&self) -> bool {
+        WaiterQueue::forwards_cancelled_wakes(self)
+    }
+
     fn waiter_count(&self) -> usize {
         WaiterQueue::waiter_count(self)
     }
@@ -13,8 +13,9 @@
 //! All implementations provide the same interface via `WaiterQueueTrait`, ensuring
 //! consistent behavior across platforms while enabling platform-specific optimizations.
 
-// Generic implementation - always compiled (used as baseline and fallback)
-mod generic;
+// Generic implementation - always compiled (used as baseline and fallback, and
+// by tests that need its FIFO wake order on every platform)
+pub(crate) mod generic;
 
 // Platform-specific modules
 // Phase 1: These re-export generic implementation
@@ -54,10 +55,16 @@ pub trait WaiterQueueTrait: Sync {
     /// After awaiting, caller should re-check the actual condition.
     ///
     /// For io_uring, can return submit() future directly.
-    /// For generic, returns immediately-ready future.
+    /// For generic, the first poll checks the condition and, if it is false,
+    /// parks the waiter in the single-waiter slot or links a node into the
+    /// intrusive waiter list. Dropping the future unlinks it and passes on a
+    /// wake it already received (see
+    /// [`forwards_cancelled_wakes`](Self::forwards_cancelled_wakes)).
     ///
-    /// **Note**: The returned future is `!Send` because io_uring operations are
-    /// thread-local. This is fine for compio's single-threaded runtime model.
+    /// **Note**: Only the io_uring backend's future is `!Send`, because
+    /// io_uring operations are thread-local; the generic backend's is `Send`.
+    /// The Linux [`WaiterQueue`] can pick either backend at runtime, so its
+    /// future is `!Send`. This is fine for compio's single-threaded runtime model.
     fn add_waiter_if<'a, F>(&'a self, condition: F) -> impl std::future::Future<Output = ()>
     where
         F: Fn() -> bool + Send + Sync + 'a;
@@ -65,7 +72,7 @@ pub trait WaiterQueueTrait: Sync {
     /// Wake one waiting task
     ///
     /// **Ordering**: Wake order is implementation-dependent and NOT guaranteed to be FIFO.
-    /// - Generic: FIFO (uses parking_lot queue)
+    /// - Generic: FIFO (intrusive waiter list)
     /// - io_uring: Unspecified (kernel scheduling)
     fn wake_one(&self);
 
@@ -75,6 +82,15 @@ pub trait WaiterQueueTrait: Sync {
     /// All waiters will be woken, but in an unspecified order.
     fn wake_all(&self);
 
+    /// Whether a waiter that was woken but dropped before completing passes
+    /// the wake on to another waiter
+    ///
+    /// Callers that re-issue lost wakes themselves only need to when this
+    /// returns `false`; otherwise the wake would be forwarded twice.
+    fn forwards_cancelled_wakes(&self) -> bool {
+        false
+    }
+
     /// Get the number of waiting tasks (for debugging/stats)
     #[allow(dead_code)]
     fn waiter_count(&self) -> usize;
@@ -0,0 +1,441 @@
+//! Single-producer, multi-consumer latest-value channel
+//!
+//! [`channel(init)`](channel) creates a cell holding one value. The [`Sender`]
+//! replaces or modifies it; every [`Receiver`] can [`borrow`](Receiver::borrow)
+//! the current value at any time and [`changed`](Receiver::changed) waits until
+//! a value newer than the last one it saw is sent. Intermediate values may be
+//! skipped: receivers only ever see the latest.
+//!
+//! Each send bumps a version counter. A receiver remembers the version it last
+//! saw, so a change made while it was busy is not lost, and waiting receivers
+//! are parked on the crate's [`WaiterQueue`].
+//!
+//! # Example
+//!
+//! Propagating a concurrency limit to a [`Semaphore`](crate::Semaphore):
+//!
+//! ```rust,no_run
+//! use compio_sync::{watch, Semaphore};
+//! use std::sync::Arc;
+//!
+//! #[compio::main]
+//! async fn main() {
+//!     let sem = Arc::new(Semaphore::new(16));
+//!     let (limit_tx, mut limit_rx) = watch::channel(16usize);
+//!
+//!     {
+//!         let sem = sem.clone();
+//!         compio::runtime::spawn(async move {
+//!             while limit_rx.changed().await.is_ok() {
+//!                 let limit = *limit_rx.borrow_and_update();
+//!                 let current = sem.max_permits();
+//!                 if limit > current {
+//!                     sem.add_permits(limit - current);
+//!                 } else {
+//!                     // Only idle permits can be removed right away
+//!                     let _ = sem.reduce_permits(current - limit);
+//!                 }
+//!             }
+//!         })
+//!         .detach();
+//!     }
+//!
+//!     // e.g. after a config reload
+//!     limit_tx.send(8).unwrap();
+//! }
+//! ```
+
+use crate::waiter_queue::WaiterQueue;
+use parking_lot::{RwLock, RwLockReadGuard};
+use std::fmt;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Create a watch channel holding `init`
+///
+/// # Example
+///
+/// ```rust
+/// use compio_sync::watch;
+///
+/// let (tx, rx) = watch::channel("v1");
+/// tx.send("v2").unwrap();
+/// assert_eq!(*rx.borrow(), "v2");
+/// ```
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        value: RwLock::new(init),
+        version: AtomicU64::new(0),
+        receivers: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+        rx_waiters: WaiterQueue::new(),
+        tx_waiters: WaiterQueue::new(),
+    });
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, seen: 0 },
+    )
+}
+
+/// State shared by the sender and all receivers
+struct Shared<T> {
+    value: RwLock<T>,
+    /// Bumped on every send, with `value` write-locked
+    version: AtomicU64,
+    /// Live `Receiver` handles
+    receivers: AtomicUsize,
+    /// Set when the sender is dropped
+    closed: AtomicBool,
+    /// Receivers waiting in `changed()`
+    rx_waiters: WaiterQueue,
+    /// The sender waiting in `closed()`
+    tx_waiters: WaiterQueue,
+}
+
+/// Sending half of a watch channel
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Replace the value and notify receivers
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`] with the value, leaving the channel unchanged, if
+    /// there are no receivers. Use [`send_replace`](Self::send_replace) to
+    /// update the value regardless.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.receiver_count() == 0 {
+            return Err(SendError(value));
+        }
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Replace the value and notify receivers, returning the previous value
+    ///
+    /// Works even with no receivers, e.g. before anyone subscribed.
+    pub fn send_replace(&self, value: T) -> T {
+        let mut previous = Some(value);
+        self.send_modify(|current| {
+            previous = Some(std::mem::replace(
+                current,
+                previous.take().expect("set above"),
+            ));
+        });
+        previous.expect("replaced by send_modify")
+    }
+
+    /// Modify the value in place and notify receivers
+    ///
+    /// Receivers are notified even if `modify` leaves the value unchanged;
+    /// see [`send_if_modified`](Self::send_if_modified).
+    pub fn send_modify<F>(&self, modify: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        self.send_if_modified(|value| {
+            modify(value);
+            true
+        });
+    }
+
+    /// Modify the value in place, notifying receivers only if `modify` returns
+    /// `true`
+    ///
+    /// Returns what `modify` returned.
+    pub fn send_if_modified<F>(&self, modify: F) -> bool
+    where
+        F: FnOnce(&mut T) -> bool,
+    {
+        {
+            let mut value = self.shared.value.write();
+            if !modify(&mut value) {
+                return false;
+            }
+            self.shared.version.fetch_add(1, Ordering::SeqCst);
+        }
+        self.shared.rx_waiters.wake_all();
+        true
+    }
+
+    /// Borrow the current value
+    ///
+    /// Sends block while the returned guard is alive; do not hold it across an
+    /// `.await`.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            guard: self.shared.value.read(),
+        }
+    }
+
+    /// Create a receiver that has seen the current value
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.shared.receivers.fetch_add(1, Ordering::SeqCst);
+        let _value = self.shared.value.read();
+        Receiver {
+            shared: self.shared.clone(),
+            seen: self.shared.version.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Get the number of live receivers
+    pub fn receiver_count(&self) -> usize {
+        self.shared.receivers.load(Ordering::SeqCst)
+    }
+
+    /// Check whether every receiver has been dropped
+    pub fn is_closed(&self) -> bool {
+        self.receiver_count() == 0
+    }
+
+    /// Wait until every receiver has been dropped
+    ///
+    /// Useful to stop a producer nobody listens to any more.
+    pub async fn closed(&self) {
+        let receivers = &self.shared.receivers;
+        while receivers.load(Ordering::SeqCst) != 0 {
+            // CRITICAL: check during registration so the last receiver
+            // dropping between the check and registering is not missed
+            self.shared
+                .tx_waiters
+                .add_waiter_if(|| receivers.load(Ordering::SeqCst) == 0)
+                .await;
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+        self.shared.rx_waiters.wake_all();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("value", &*self.borrow())
+            .field("receivers", &self.receiver_count())
+            .finish()
+    }
+}
+
+/// Receiving half of a watch channel; clone it to add consumers
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    /// Version of the value this receiver last saw
+    seen: u64,
+}
+
+impl<T> Receiver<T> {
+    /// Borrow the current value without marking it seen
+    ///
+    /// Sends block while the returned guard is alive; do not hold it across an
+    /// `.await`.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            guard: self.shared.value.read(),
+        }
+    }
+
+    /// Borrow the current value and mark it seen
+    ///
+    /// The next [`changed`](Self::changed) then waits for a newer value.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        let guard = self.shared.value.read();
+        // The version cannot move while the value is read-locked
+        self.seen = self.shared.version.load(Ordering::SeqCst);
+        Ref { guard }
+    }
+
+    /// Check whether a value newer than the last one seen was sent
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError`] if the sender was dropped.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        if self.shared.closed.load(Ordering::SeqCst) {
+            return Err(RecvError(()));
+        }
+        Ok(self.shared.version.load(Ordering::SeqCst) != self.seen)
+    }
+
+    /// Wait for a value newer than the last one seen, and mark it seen
+    ///
+    /// Returns immediately if such a value was sent already. Read it with
+    /// [`borrow`](Self::borrow) or [`borrow_and_update`](Self::borrow_and_update).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError`] if the sender was dropped and there is no unseen
+    /// value.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        let (version, closed, seen) = (&self.shared.version, &self.shared.closed, self.seen);
+        loop {
+            // Read `closed` first: a send before the sender dropped is then
+            // visible in `version`
+            let is_closed = closed.load(Ordering::SeqCst);
+            let current = version.load(Ordering::SeqCst);
+            if current != seen {
+                self.seen = current;
+                return Ok(());
+            }
+            if is_closed {
+                return Err(RecvError(()));
+            }
+
+            // CRITICAL: check during registration so a send or the sender
+            // dropping between the checks above and registering is not missed
+            self.shared
+                .rx_waiters
+                .add_waiter_if(|| {
+                    version.load(Ordering::SeqCst) != seen || closed.load(Ordering::SeqCst)
+                })
+                .await;
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    /// Create another receiver that has seen the same version as this one
+    fn clone(&self) -> Self {
+        self.shared.receivers.fetch_add(1, Ordering::SeqCst);
+        Self {
+            shared: self.shared.clone(),
+            seen: self.seen,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if self.shared.receivers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shared.tx_waiters.wake_all();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("value", &*self.borrow())
+            .finish()
+    }
+}
+
+/// Borrowed value of a watch channel, from `borrow()`
+///
+/// Holds a read lock: sends wait until it is dropped.
+pub struct Ref<'a, T> {
+    guard: RwLockReadGuard<'a, T>,
+}
+
+impl<'a, T> Deref for Ref<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Ref<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Error returned by `Sender::send()` when there are no receivers, holding the
+/// value
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel has no receivers")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+/// Error returned by `Receiver::changed()` when the sender was dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError(());
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sender dropped")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_track_seen_value() {
+        let (tx, mut rx) = channel(0);
+        assert_eq!(rx.has_changed(), Ok(false));
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(rx.has_changed(), Ok(true));
+        // Only the latest value is kept
+        assert_eq!(*rx.borrow_and_update(), 2);
+        assert_eq!(rx.has_changed(), Ok(false));
+    }
+
+    #[test]
+    fn test_send_if_modified_skips_unchanged() {
+        let (tx, rx) = channel(5);
+        assert!(!tx.send_if_modified(|v| {
+            let changed = *v != 5;
+            *v = 5;
+            changed
+        }));
+        assert_eq!(rx.has_changed(), Ok(false));
+
+        assert_eq!(tx.send_replace(6), 5);
+        assert_eq!(rx.has_changed(), Ok(true));
+    }
+
+    #[test]
+    fn test_send_without_receivers() {
+        let (tx, rx) = channel(String::new());
+        drop(rx);
+        assert!(tx.is_closed());
+        assert_eq!(
+            tx.send(String::from("a")),
+            Err(SendError(String::from("a")))
+        );
+
+        // Still updates the value for later subscribers
+        tx.send_replace(String::from("b"));
+        assert_eq!(*tx.subscribe().borrow(), "b");
+    }
+
+    #[test]
+    fn test_clone_keeps_seen_version() {
+        let (tx, mut rx) = channel(0);
+        tx.send(1).unwrap();
+        let unseen = rx.clone();
+        rx.borrow_and_update();
+        let seen = rx.clone();
+
+        assert_eq!(unseen.has_changed(), Ok(true));
+        assert_eq!(seen.has_changed(), Ok(false));
+        assert_eq!(tx.receiver_count(), 3);
+    }
+}
@@ -0,0 +1,119 @@
+//! Integration tests for AdaptiveLimiter
+
+use compio_sync::{AdaptiveLimiter, LimitAlgorithm};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Standard timeout for tests to prevent indefinite hangs
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_limiter_bounds_concurrency() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let limiter = Arc::new(AdaptiveLimiter::new(4, 1, 4, LimitAlgorithm::aimd()));
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut handles = vec![];
+
+        for _ in 0..20 {
+            let limiter = limiter.clone();
+            let running = running.clone();
+            let peak = peak.clone();
+            handles.push(compio::runtime::spawn(async move {
+                let permit = limiter.acquire().await.unwrap();
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                compio::time::sleep(Duration::from_millis(5)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                permit.success();
+            }));
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert!(peak.load(Ordering::SeqCst) <= 4);
+        assert_eq!(limiter.limit(), 4, "ceiling must hold");
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_limiter_backs_off_and_recovers() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let limiter = Arc::new(AdaptiveLimiter::new(8, 2, 16, LimitAlgorithm::aimd()));
+
+        // Simulated EMFILE storm while fully loaded: shrink towards the floor
+        let permits: Vec<_> = (0..8).map(|_| limiter.try_acquire().unwrap()).collect();
+        for permit in permits {
+            permit.overload();
+        }
+        assert!(limiter.limit() < 8);
+        assert!(limiter.limit() >= 2);
+        let backed_off = limiter.limit();
+
+        // Pressure gone: successes at full utilisation probe upwards again
+        for _ in 0..4 {
+            let permits: Vec<_> = (0..limiter.limit())
+                .map(|_| limiter.try_acquire().unwrap())
+                .collect();
+            for permit in permits {
+                permit.success();
+            }
+        }
+        assert!(limiter.limit() > backed_off);
+        assert!(limiter.limit() <= 16);
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_limiter_shrink_wakes_waiters_after_release() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let limiter = Arc::new(AdaptiveLimiter::new(2, 1, 4, LimitAlgorithm::aimd()));
+        let a = limiter.try_acquire().unwrap();
+        let b = limiter.try_acquire().unwrap();
+
+        let waiter = {
+            let limiter = limiter.clone();
+            compio::runtime::spawn(async move {
+                limiter.acquire().await.unwrap().ignore();
+            })
+        };
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        // Backing off to 1 absorbs `a`; releasing `b` then admits the waiter
+        a.overload();
+        assert_eq!(limiter.limit(), 1);
+        b.ignore();
+
+        waiter.await.unwrap();
+        assert_eq!(limiter.in_flight(), 0);
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_limiter_close_fails_waiters() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let limiter = Arc::new(AdaptiveLimiter::new(1, 1, 1, LimitAlgorithm::gradient()));
+        let held = limiter.try_acquire().unwrap();
+
+        let waiter = {
+            let limiter = limiter.clone();
+            compio::runtime::spawn(async move { limiter.acquire().await.is_err() })
+        };
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        limiter.close();
+        assert!(waiter.await.unwrap());
+        drop(held);
+    })
+    .await
+    .expect("test timed out");
+}
@@ -0,0 +1,110 @@
+//! Integration tests for Barrier
+
+use compio_sync::Barrier;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout for barrier tests to prevent hanging
+const BARRIER_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_barrier_one_leader_per_generation() {
+    let result = compio::time::timeout(BARRIER_TEST_TIMEOUT, async {
+        let barrier = Arc::new(Barrier::new(8));
+        let mut handles = vec![];
+
+        for _ in 0..8 {
+            let barrier = barrier.clone();
+            handles.push(compio::runtime::spawn(async move {
+                let mut leaders = 0;
+                for _ in 0..5 {
+                    if barrier.wait().await.is_leader() {
+                        leaders += 1;
+                    }
+                }
+                leaders
+            }));
+        }
+
+        let mut total = 0;
+        for handle in handles {
+            total += handle.await.unwrap();
+        }
+        assert_eq!(total, 5, "expected one leader per generation");
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        BARRIER_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_barrier_waits_for_all_tasks() {
+    let result = compio::time::timeout(BARRIER_TEST_TIMEOUT, async {
+        let barrier = Arc::new(Barrier::new(3));
+        let passed = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..2 {
+            let barrier = barrier.clone();
+            let passed = passed.clone();
+            compio::runtime::spawn(async move {
+                barrier.wait().await;
+                passed.fetch_add(1, Ordering::SeqCst);
+            })
+            .detach();
+        }
+        compio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(passed.load(Ordering::SeqCst), 0);
+
+        // The last arrival releases everyone
+        assert!(barrier.wait().await.is_leader());
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(passed.load(Ordering::SeqCst), 2);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        BARRIER_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_barrier_phases_do_not_overlap() {
+    let result = compio::time::timeout(BARRIER_TEST_TIMEOUT, async {
+        const TASKS: usize = 4;
+        let barrier = Arc::new(Barrier::new(TASKS));
+        let scanned = Arc::new(AtomicUsize::new(0));
+        let mut handles = vec![];
+
+        for i in 0..TASKS {
+            let barrier = barrier.clone();
+            let scanned = scanned.clone();
+            handles.push(compio::runtime::spawn(async move {
+                // Stagger the scan phase
+                compio::time::sleep(Duration::from_millis(i as u64 * 3)).await;
+                scanned.fetch_add(1, Ordering::SeqCst);
+
+                barrier.wait().await;
+                // Copy phase: every scan has finished
+                scanned.load(Ordering::SeqCst)
+            }));
+        }
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), TASKS);
+        }
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        BARRIER_TEST_TIMEOUT
+    );
+}
@@ -0,0 +1,103 @@
+//! Integration tests for the broadcast channel
+
+use compio_sync::broadcast;
+use std::time::Duration;
+
+/// Timeout for broadcast tests to prevent hanging
+const BROADCAST_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_broadcast_every_receiver_sees_every_message() {
+    let result = compio::time::timeout(BROADCAST_TEST_TIMEOUT, async {
+        let (tx, rx) = broadcast::channel(16);
+        let mut handles = vec![];
+
+        let mut receivers = vec![rx];
+        for _ in 0..3 {
+            receivers.push(tx.subscribe());
+        }
+        for mut rx in receivers {
+            handles.push(compio::runtime::spawn(async move {
+                let mut seen = Vec::new();
+                while let Ok(value) = rx.recv().await {
+                    seen.push(value);
+                }
+                seen
+            }));
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        for i in 0..10 {
+            assert_eq!(tx.send(i).unwrap(), 4);
+            // Let receivers keep up so nobody lags
+            compio::time::sleep(Duration::from_millis(1)).await;
+        }
+        drop(tx);
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), (0..10).collect::<Vec<_>>());
+        }
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        BROADCAST_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_broadcast_slow_receiver_lags_without_blocking_sender() {
+    let result = compio::time::timeout(BROADCAST_TEST_TIMEOUT, async {
+        let (tx, mut slow) = broadcast::channel(4);
+
+        // The sender is never blocked by the slow receiver
+        for i in 0..10 {
+            tx.send(i).unwrap();
+        }
+
+        assert_eq!(slow.recv().await, Err(broadcast::RecvError::Lagged(6)));
+        for expected in 6..10 {
+            assert_eq!(slow.recv().await, Ok(expected));
+        }
+        drop(tx);
+        assert_eq!(slow.recv().await, Err(broadcast::RecvError::Closed));
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        BROADCAST_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_broadcast_waiting_receivers_woken_by_send() {
+    let result = compio::time::timeout(BROADCAST_TEST_TIMEOUT, async {
+        let (tx, rx) = broadcast::channel(1);
+        let mut handles = vec![];
+
+        let mut receivers = vec![rx];
+        for _ in 0..7 {
+            receivers.push(tx.subscribe());
+        }
+        for mut rx in receivers {
+            handles.push(compio::runtime::spawn(async move { rx.recv().await }));
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        tx.send("go").unwrap();
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), Ok("go"));
+        }
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        BROADCAST_TEST_TIMEOUT
+    );
+}
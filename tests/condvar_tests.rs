@@ -124,17 +124,18 @@ async fn test_condvar_notify_before_wait() {
     // Notify before anyone is waiting
     cv.notify_all();
 
-    // Subsequent wait should complete immediately
+    // The notification is not stored: a later wait blocks
     let cv_clone = cv.clone();
     let handle = compio::runtime::spawn(async move {
-        cv_clone.wait().await;
-        "done"
+        compio::time::timeout(Duration::from_millis(50), cv_clone.wait())
+            .await
+            .is_err()
     });
 
-    // Should complete quickly
-    let result = compio::time::timeout(Duration::from_millis(100), handle).await;
-    assert!(result.is_ok());
-    assert_eq!(result.unwrap().unwrap(), "done");
+    assert!(
+        handle.await.unwrap(),
+        "wait saw a notification from before it started"
+    );
 }
 
 #[compio::test]
@@ -288,3 +289,164 @@ async fn test_condvar_sequential_notifications() {
         CONDVAR_TEST_TIMEOUT
     );
 }
+
+#[compio::test]
+async fn test_condvar_wait_guard_queue() {
+    let result = compio::time::timeout(CONDVAR_TEST_TIMEOUT, async {
+        let queue = Arc::new(compio_sync::Mutex::new(Vec::new()));
+        let cv = Arc::new(Condvar::new());
+        let mut consumers = vec![];
+
+        for _ in 0..4 {
+            let queue = queue.clone();
+            let cv = cv.clone();
+            consumers.push(compio::runtime::spawn(async move {
+                let mut taken = 0;
+                loop {
+                    let mut items = queue.lock().await;
+                    while items.is_empty() {
+                        items = cv.wait_guard(items).await;
+                    }
+                    match items.pop() {
+                        Some(Some(_)) => taken += 1,
+                        // Poison pill: stop
+                        Some(None) => return taken,
+                        None => unreachable!("queue checked non-empty under the lock"),
+                    }
+                }
+            }));
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        for i in 0..100 {
+            queue.lock().await.push(Some(i));
+            cv.notify_one();
+            if i % 10 == 0 {
+                compio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+        // Let the consumers drain before stopping them
+        while queue.lock().await.iter().any(Option::is_some) {
+            compio::time::sleep(Duration::from_millis(1)).await;
+        }
+        for _ in 0..4 {
+            queue.lock().await.insert(0, None);
+        }
+        cv.notify_all();
+
+        let mut total = 0;
+        for consumer in consumers {
+            total += consumer.await.unwrap();
+        }
+        assert_eq!(total, 100);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        CONDVAR_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_condvar_wait_until_counter() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let result = compio::time::timeout(CONDVAR_TEST_TIMEOUT, async {
+        let cv = Arc::new(Condvar::new());
+        let done = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..10 {
+            let cv = cv.clone();
+            let done = done.clone();
+            compio::runtime::spawn(async move {
+                compio::time::sleep(Duration::from_millis(5)).await;
+                done.fetch_add(1, Ordering::SeqCst);
+                cv.notify_all();
+            })
+            .detach();
+        }
+
+        cv.wait_until(|| done.load(Ordering::SeqCst) == 10).await;
+        assert_eq!(done.load(Ordering::SeqCst), 10);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        CONDVAR_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_condvar_wait_while_timeout() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let result = compio::time::timeout(CONDVAR_TEST_TIMEOUT, async {
+        let cv = Arc::new(Condvar::new());
+        let busy = Arc::new(AtomicBool::new(true));
+
+        // Nobody clears `busy`: times out
+        let satisfied = cv
+            .wait_while_timeout(|| busy.load(Ordering::Acquire), Duration::from_millis(20))
+            .await;
+        assert!(!satisfied);
+
+        // Cleared by another task well within the timeout
+        {
+            let cv = cv.clone();
+            let busy = busy.clone();
+            compio::runtime::spawn(async move {
+                compio::time::sleep(Duration::from_millis(10)).await;
+                busy.store(false, Ordering::Release);
+                cv.notify_one();
+            })
+            .detach();
+        }
+        let satisfied = cv
+            .wait_while_timeout(|| busy.load(Ordering::Acquire), Duration::from_secs(5))
+            .await;
+        assert!(satisfied);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        CONDVAR_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_condvar_wait_guard_owned() {
+    let result = compio::time::timeout(CONDVAR_TEST_TIMEOUT, async {
+        let ready = Arc::new(compio_sync::Mutex::new(false));
+        let cv = Arc::new(Condvar::new());
+
+        let waiter = {
+            let ready = ready.clone();
+            let cv = cv.clone();
+            compio::runtime::spawn(async move {
+                let mut guard = ready.lock_owned().await;
+                while !*guard {
+                    guard = cv.wait_guard(guard).await;
+                }
+                *guard
+            })
+        };
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        *ready.lock().await = true;
+        cv.notify_one();
+        assert!(waiter.await.unwrap());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        CONDVAR_TEST_TIMEOUT
+    );
+}
@@ -0,0 +1,136 @@
+//! Integration tests for ManualResetEvent and AutoResetEvent
+
+use compio_sync::{AutoResetEvent, ManualResetEvent};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout for event tests to prevent hanging
+const EVENT_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_manual_reset_releases_all_waiters() {
+    let result = compio::time::timeout(EVENT_TEST_TIMEOUT, async {
+        let event = Arc::new(ManualResetEvent::new(false));
+        let mut handles = vec![];
+
+        for i in 0..10 {
+            let event = event.clone();
+            handles.push(compio::runtime::spawn(async move {
+                event.wait().await;
+                i
+            }));
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        event.set();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.await.unwrap(), i);
+        }
+
+        // Stays set for later waiters
+        event.wait().await;
+        assert!(event.is_set());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        EVENT_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_manual_reset_blocks_after_reset() {
+    let event = ManualResetEvent::new(true);
+    event.wait().await;
+
+    event.reset();
+    let result = compio::time::timeout(Duration::from_millis(20), event.wait()).await;
+    assert!(result.is_err());
+}
+
+#[compio::test]
+async fn test_auto_reset_single_consumer() {
+    let result = compio::time::timeout(EVENT_TEST_TIMEOUT, async {
+        let event = Arc::new(AutoResetEvent::new(false));
+        let released = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..5 {
+            let event = event.clone();
+            let released = released.clone();
+            compio::runtime::spawn(async move {
+                event.wait().await;
+                released.fetch_add(1, Ordering::SeqCst);
+            })
+            .detach();
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        // Back-to-back sets do not accumulate: one waiter is released
+        event.set();
+        event.set();
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(released.load(Ordering::SeqCst), 1);
+
+        for expected in 2..=5 {
+            event.set();
+            compio::time::sleep(Duration::from_millis(10)).await;
+            assert_eq!(released.load(Ordering::SeqCst), expected);
+        }
+        assert!(!event.is_set());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        EVENT_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_auto_reset_signal_kept_for_next_wait() {
+    let event = AutoResetEvent::new(false);
+    event.set();
+    assert!(event.is_set());
+
+    // Consumed by the first wait only
+    event.wait().await;
+    assert!(!event.is_set());
+    let result = compio::time::timeout(Duration::from_millis(20), event.wait()).await;
+    assert!(result.is_err());
+}
+
+#[compio::test]
+async fn test_auto_reset_cancelled_waiter_does_not_lose_signal() {
+    let result = compio::time::timeout(EVENT_TEST_TIMEOUT, async {
+        let event = Arc::new(AutoResetEvent::new(false));
+
+        // A waiter that gives up, queued ahead of one that does not
+        let impatient = {
+            let event = event.clone();
+            compio::runtime::spawn(async move {
+                let _ = compio::time::timeout(Duration::from_millis(20), event.wait()).await;
+            })
+        };
+        compio::time::sleep(Duration::from_millis(5)).await;
+        let patient = {
+            let event = event.clone();
+            compio::runtime::spawn(async move { event.wait().await })
+        };
+        impatient.await.unwrap();
+
+        event.set();
+        patient.await.unwrap();
+        assert!(!event.is_set());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        EVENT_TEST_TIMEOUT
+    );
+}
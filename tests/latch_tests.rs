@@ -0,0 +1,132 @@
+//! Integration tests for CountDownLatch and WaitGroup
+
+use compio_sync::{CountDownLatch, WaitGroup};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout for latch tests to prevent hanging
+const LATCH_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_latch_releases_all_waiters_at_zero() {
+    let result = compio::time::timeout(LATCH_TEST_TIMEOUT, async {
+        let latch = Arc::new(CountDownLatch::new(3));
+        let released = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..5 {
+            let latch = latch.clone();
+            let released = released.clone();
+            compio::runtime::spawn(async move {
+                latch.wait().await;
+                released.fetch_add(1, Ordering::SeqCst);
+            })
+            .detach();
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        latch.count_down();
+        latch.count_down();
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(released.load(Ordering::SeqCst), 0);
+
+        latch.count_down();
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(released.load(Ordering::SeqCst), 5);
+
+        // Stays open
+        latch.wait().await;
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        LATCH_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_latch_counted_down_by_tasks() {
+    let result = compio::time::timeout(LATCH_TEST_TIMEOUT, async {
+        let latch = Arc::new(CountDownLatch::new(20));
+        let done = Arc::new(AtomicUsize::new(0));
+
+        for i in 0..20 {
+            let latch = latch.clone();
+            let done = done.clone();
+            compio::runtime::spawn(async move {
+                compio::time::sleep(Duration::from_millis(i % 5)).await;
+                done.fetch_add(1, Ordering::SeqCst);
+                latch.count_down();
+            })
+            .detach();
+        }
+
+        latch.wait().await;
+        assert_eq!(done.load(Ordering::SeqCst), 20);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        LATCH_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_wait_group_waits_for_spawned_tasks() {
+    let result = compio::time::timeout(LATCH_TEST_TIMEOUT, async {
+        let wg = WaitGroup::new();
+        let done = Arc::new(AtomicUsize::new(0));
+
+        for i in 0..50 {
+            let member = wg.clone();
+            let done = done.clone();
+            compio::runtime::spawn(async move {
+                compio::time::sleep(Duration::from_millis(i % 7)).await;
+                done.fetch_add(1, Ordering::SeqCst);
+                drop(member);
+            })
+            .detach();
+        }
+
+        wg.wait().await;
+        assert_eq!(done.load(Ordering::SeqCst), 50);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        LATCH_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_wait_group_multiple_waiters() {
+    let result = compio::time::timeout(LATCH_TEST_TIMEOUT, async {
+        let wg = WaitGroup::new();
+        let worker = wg.clone();
+
+        // A second waiter is itself a member, so it drops out in wait()
+        let observer = {
+            let wg = wg.clone();
+            compio::runtime::spawn(async move { wg.wait().await })
+        };
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(worker.count(), 2);
+
+        drop(worker);
+        wg.wait().await;
+        observer.await.unwrap();
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        LATCH_TEST_TIMEOUT
+    );
+}
@@ -14,7 +14,7 @@ async fn test_linux_semaphore_basic() {
 
     // Add timeout to prevent hanging
     let result = compio::time::timeout(Duration::from_secs(5), async {
-        let permit = sem.acquire().await;
+        let permit = sem.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 0);
 
         drop(permit);
@@ -39,7 +39,7 @@ async fn test_linux_high_concurrency() {
     for i in 0..100 {
         let sem = sem.clone();
         handles.push(compio::runtime::spawn(async move {
-            let _p = sem.acquire().await;
+            let _p = sem.acquire().await.unwrap();
             i
         }));
     }
@@ -62,14 +62,14 @@ async fn test_linux_futex_wake_all() {
     let sem = Arc::new(Semaphore::new(1));
 
     // Hold the permit
-    let permit = sem.acquire().await;
+    let permit = sem.acquire().await.unwrap();
 
     // Spawn multiple waiters
     let mut handles = vec![];
     for i in 0..10 {
         let sem = sem.clone();
         handles.push(compio::runtime::spawn(async move {
-            let _p = sem.acquire().await;
+            let _p = sem.acquire().await.unwrap();
             i
         }));
     }
@@ -93,7 +93,7 @@ fn test_kernel_version_detection() {
 
     // Try to acquire - this should work regardless of implementation
     let permit = sem.try_acquire();
-    assert!(permit.is_some());
+    assert!(permit.is_ok());
     assert_eq!(sem.available_permits(), 0);
 
     // Release and verify
@@ -127,7 +127,7 @@ async fn test_linux_mixed_io_and_sync() {
         let sem = sem.clone();
         handles.push(compio::runtime::spawn(async move {
             // Acquire permit (sync primitive)
-            let _p = sem.acquire().await;
+            let _p = sem.acquire().await.unwrap();
 
             // Do some I/O (file operation)
             // This goes through io_uring
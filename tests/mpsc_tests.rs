@@ -0,0 +1,156 @@
+//! Integration tests for the bounded mpsc channel
+
+use compio_sync::mpsc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout for channel tests to prevent hanging
+const MPSC_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_mpsc_many_producers() {
+    let result = compio::time::timeout(MPSC_TEST_TIMEOUT, async {
+        let (tx, mut rx) = mpsc::channel(4);
+
+        for producer in 0..8 {
+            let tx = tx.clone();
+            compio::runtime::spawn(async move {
+                for i in 0..50 {
+                    tx.send(producer * 1000 + i).await.unwrap();
+                }
+            })
+            .detach();
+        }
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Some(value) = rx.recv().await {
+            received.push(value);
+        }
+        assert_eq!(received.len(), 400);
+
+        // Each producer's messages arrive in order
+        for producer in 0..8 {
+            let own: Vec<_> = received.iter().filter(|v| **v / 1000 == producer).collect();
+            assert!(own.windows(2).all(|w| w[0] < w[1]));
+        }
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        MPSC_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_mpsc_backpressure() {
+    let result = compio::time::timeout(MPSC_TEST_TIMEOUT, async {
+        let (tx, mut rx) = mpsc::channel(2);
+        let sent = Arc::new(AtomicUsize::new(0));
+
+        {
+            let sent = sent.clone();
+            compio::runtime::spawn(async move {
+                for i in 0..5 {
+                    tx.send(i).await.unwrap();
+                    sent.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .detach();
+        }
+        compio::time::sleep(Duration::from_millis(20)).await;
+        // Producer is paused at capacity
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+
+        assert_eq!(rx.recv().await, Some(0));
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(sent.load(Ordering::SeqCst), 3);
+
+        let mut rest = Vec::new();
+        while let Some(value) = rx.recv().await {
+            rest.push(value);
+        }
+        assert_eq!(rest, vec![1, 2, 3, 4]);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        MPSC_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_mpsc_close_fails_waiting_sender() {
+    let result = compio::time::timeout(MPSC_TEST_TIMEOUT, async {
+        let (tx, mut rx) = mpsc::channel(1);
+        tx.send("buffered").await.unwrap();
+
+        let blocked = compio::runtime::spawn(async move { tx.send("blocked").await });
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        rx.close();
+        assert_eq!(blocked.await.unwrap(), Err(mpsc::SendError("blocked")));
+
+        // Buffered messages survive the close
+        assert_eq!(rx.recv().await, Some("buffered"));
+        assert_eq!(rx.recv().await, None);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        MPSC_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_mpsc_recv_many_batches() {
+    let result = compio::time::timeout(MPSC_TEST_TIMEOUT, async {
+        let (tx, mut rx) = mpsc::channel(16);
+        for i in 0..10 {
+            tx.send(i).await.unwrap();
+        }
+        drop(tx);
+
+        let mut batch = Vec::new();
+        assert_eq!(rx.recv_many(&mut batch, 4).await, 4);
+        assert_eq!(rx.recv_many(&mut batch, 100).await, 6);
+        assert_eq!(batch, (0..10).collect::<Vec<_>>());
+        assert_eq!(rx.recv_many(&mut batch, 4).await, 0);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        MPSC_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_mpsc_reserve_then_send() {
+    let result = compio::time::timeout(MPSC_TEST_TIMEOUT, async {
+        let (tx, mut rx) = mpsc::channel(1);
+        let permit = tx.reserve().await.unwrap();
+        assert!(matches!(tx.try_send(0), Err(mpsc::TrySendError::Full(0))));
+
+        // The receiver waits for the reserved message
+        let receiver = compio::runtime::spawn(async move { rx.recv().await });
+        compio::time::sleep(Duration::from_millis(10)).await;
+        permit.send(7);
+        assert_eq!(receiver.await.unwrap(), Some(7));
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        MPSC_TEST_TIMEOUT
+    );
+}
@@ -0,0 +1,142 @@
+//! Integration tests for Mutex
+
+use compio_sync::{MappedMutexGuard, Mutex, MutexGuard};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Standard timeout for tests to prevent indefinite hangs
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_mutex_held_across_await() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let mutex = Arc::new(Mutex::new(0usize));
+        let inside = Arc::new(AtomicUsize::new(0));
+        let mut handles = vec![];
+
+        for _ in 0..20 {
+            let mutex = mutex.clone();
+            let inside = inside.clone();
+            handles.push(compio::runtime::spawn(async move {
+                let mut guard = mutex.lock().await;
+                assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0, "two holders");
+                compio::time::sleep(Duration::from_millis(1)).await;
+                *guard += 1;
+                inside.fetch_sub(1, Ordering::SeqCst);
+            }));
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(*mutex.lock().await, 20);
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_owned_guard_moves_into_task() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let mutex = Arc::new(Mutex::new(Vec::new()));
+        let mut guard = mutex.clone().lock_owned().await;
+
+        let handle = compio::runtime::spawn(async move {
+            compio::time::sleep(Duration::from_millis(10)).await;
+            guard.push("from task");
+        });
+
+        // Blocks until the task drops its guard
+        let contents = mutex.lock().await.clone();
+        assert_eq!(contents, vec!["from task"]);
+        handle.await.unwrap();
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_mapped_guard_across_await() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let mutex = Arc::new(Mutex::new((0u64, 0u64)));
+
+        let writer = {
+            let mutex = mutex.clone();
+            compio::runtime::spawn(async move {
+                let mut bytes = MutexGuard::map(mutex.lock().await, |stats| &mut stats.1);
+                compio::time::sleep(Duration::from_millis(10)).await;
+                *bytes += 4096;
+            })
+        };
+        compio::time::sleep(Duration::from_millis(1)).await;
+
+        assert_eq!(*mutex.lock().await, (0, 4096));
+        writer.await.unwrap();
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[test]
+fn test_panicking_map_releases_lock() {
+    let mutex = Mutex::new((0u64, 0u64));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        MutexGuard::map(mutex.try_lock().unwrap(), |_| -> &mut u64 {
+            panic!("map failed")
+        })
+    }));
+    assert!(result.is_err());
+    assert!(
+        mutex.try_lock().is_ok(),
+        "mutex left locked after panic in map"
+    );
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let bytes = MutexGuard::map(mutex.try_lock().unwrap(), |stats| &mut stats.1);
+        MappedMutexGuard::map(bytes, |_| -> &mut u64 { panic!("map failed") })
+    }));
+    assert!(result.is_err());
+    assert!(
+        mutex.try_lock().is_ok(),
+        "mutex left locked after panic in map"
+    );
+}
+
+#[compio::test]
+async fn test_cancelled_lock_does_not_strand_waiters() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let mutex = Arc::new(Mutex::new(()));
+        let guard = mutex.lock().await;
+
+        // Waiters that give up while the lock is held
+        let mut quitters = vec![];
+        for _ in 0..5 {
+            let mutex = mutex.clone();
+            quitters.push(compio::runtime::spawn(async move {
+                compio::time::timeout(Duration::from_millis(20), mutex.lock())
+                    .await
+                    .is_err()
+            }));
+        }
+        let survivor = {
+            let mutex = mutex.clone();
+            compio::runtime::spawn(async move {
+                drop(mutex.lock().await);
+            })
+        };
+
+        compio::time::sleep(Duration::from_millis(5)).await;
+        drop(guard);
+
+        survivor.await.unwrap();
+        for quitter in quitters {
+            quitter.await.unwrap();
+        }
+        assert!(!mutex.is_locked());
+    })
+    .await
+    .expect("test timed out");
+}
@@ -0,0 +1,155 @@
+//! Integration tests for Notify
+
+use compio_sync::Notify;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Waker};
+use std::time::Duration;
+
+/// Standard timeout for tests to prevent indefinite hangs
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_notify_one_wakes_exactly_one() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let notify = Arc::new(Notify::new());
+        let woken = Arc::new(AtomicUsize::new(0));
+        let mut handles = vec![];
+
+        for _ in 0..5 {
+            let notify = notify.clone();
+            let woken = woken.clone();
+            handles.push(compio::runtime::spawn(async move {
+                notify.notified().await;
+                woken.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        notify.notify_one();
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(woken.load(Ordering::SeqCst), 1);
+
+        // Release the rest one at a time
+        for expected in 2..=5 {
+            notify.notify_one();
+            compio::time::sleep(Duration::from_millis(10)).await;
+            assert_eq!(woken.load(Ordering::SeqCst), expected);
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_notify_waiters_only_wakes_current() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let notify = Arc::new(Notify::new());
+        let mut handles = vec![];
+
+        for _ in 0..3 {
+            let notify = notify.clone();
+            handles.push(compio::runtime::spawn(async move {
+                notify.notified().await;
+            }));
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        notify.notify_waiters();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // A later waiter is not released by the earlier notify_waiters()
+        let late = compio::time::timeout(Duration::from_millis(20), notify.notified()).await;
+        assert!(late.is_err());
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_enable_before_checking_state() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let notify = Arc::new(Notify::new());
+        let ready = Arc::new(AtomicBool::new(false));
+
+        let setter = {
+            let notify = notify.clone();
+            let ready = ready.clone();
+            compio::runtime::spawn(async move {
+                compio::time::sleep(Duration::from_millis(5)).await;
+                ready.store(true, Ordering::Release);
+                notify.notify_waiters();
+            })
+        };
+
+        loop {
+            let mut notified = pin!(notify.notified());
+            notified.as_mut().enable();
+            if ready.load(Ordering::Acquire) {
+                break;
+            }
+            notified.await;
+        }
+        setter.await.unwrap();
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_cancelled_waiter_forwards_notification() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let notify = Arc::new(Notify::new());
+
+        let quitter = {
+            let notify = notify.clone();
+            compio::runtime::spawn(async move {
+                compio::time::timeout(Duration::from_millis(30), notify.notified())
+                    .await
+                    .is_err()
+            })
+        };
+        let survivor = {
+            let notify = notify.clone();
+            compio::runtime::spawn(async move { notify.notified().await })
+        };
+        compio::time::sleep(Duration::from_millis(5)).await;
+
+        assert!(quitter.await.unwrap());
+        notify.notify_one();
+        survivor.await.unwrap();
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_late_waiter_cannot_take_earlier_notification() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let notify = Notify::new();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut early = pin!(notify.notified());
+        assert!(early.as_mut().poll(&mut cx).is_pending());
+
+        notify.notify_one();
+
+        // Registered after the notification: it is not for this one
+        let mut late = pin!(notify.notified());
+        assert!(late.as_mut().poll(&mut cx).is_pending());
+        assert!(early.as_mut().poll(&mut cx).is_ready());
+        assert!(late.as_mut().poll(&mut cx).is_pending());
+
+        notify.notify_one();
+        late.await;
+    })
+    .await
+    .expect("test timed out");
+}
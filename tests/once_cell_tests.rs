@@ -0,0 +1,149 @@
+//! Integration tests for OnceCell and Lazy
+
+use compio_sync::{Lazy, OnceCell};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout for once-cell tests to prevent hanging
+const ONCE_CELL_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_once_cell_racing_initialisers_run_once() {
+    let result = compio::time::timeout(ONCE_CELL_TEST_TIMEOUT, async {
+        let cell = Arc::new(OnceCell::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut handles = vec![];
+
+        for i in 0..20 {
+            let cell = cell.clone();
+            let calls = calls.clone();
+            handles.push(compio::runtime::spawn(async move {
+                *cell
+                    .get_or_init(|| async {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        // Slow initialiser so the others have to wait
+                        compio::time::sleep(Duration::from_millis(20)).await;
+                        i
+                    })
+                    .await
+            }));
+        }
+
+        let first = handles.remove(0).await.unwrap();
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), first);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        ONCE_CELL_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_once_cell_failed_init_lets_waiter_retry() {
+    let result = compio::time::timeout(ONCE_CELL_TEST_TIMEOUT, async {
+        let cell = Arc::new(OnceCell::new());
+
+        let failing = {
+            let cell = cell.clone();
+            compio::runtime::spawn(async move {
+                cell.get_or_try_init(|| async {
+                    compio::time::sleep(Duration::from_millis(20)).await;
+                    Err("config missing")
+                })
+                .await
+                .copied()
+            })
+        };
+        compio::time::sleep(Duration::from_millis(5)).await;
+
+        // Waits for the failing initialiser, then runs its own
+        let value = cell
+            .get_or_try_init(|| async { Ok::<_, &str>(3) })
+            .await
+            .unwrap();
+        assert_eq!(*value, 3);
+        assert_eq!(failing.await.unwrap(), Err("config missing"));
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        ONCE_CELL_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_once_cell_cancelled_init_lets_waiter_retry() {
+    let result = compio::time::timeout(ONCE_CELL_TEST_TIMEOUT, async {
+        let cell = Arc::new(OnceCell::new());
+
+        let cancelled = {
+            let cell = cell.clone();
+            compio::runtime::spawn(async move {
+                let init = cell.get_or_init(|| async {
+                    compio::time::sleep(Duration::from_secs(60)).await;
+                    1
+                });
+                compio::time::timeout(Duration::from_millis(20), init)
+                    .await
+                    .is_err()
+            })
+        };
+        compio::time::sleep(Duration::from_millis(5)).await;
+
+        assert_eq!(*cell.get_or_init(|| async { 2 }).await, 2);
+        assert!(cancelled.await.unwrap());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        ONCE_CELL_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_lazy_shared_between_tasks() {
+    let result = compio::time::timeout(ONCE_CELL_TEST_TIMEOUT, async {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let lazy = {
+            let calls = calls.clone();
+            Arc::new(Lazy::new(move || {
+                let calls = calls.clone();
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    compio::time::sleep(Duration::from_millis(10)).await;
+                    String::from("ready")
+                }
+            }))
+        };
+
+        let mut handles = vec![];
+        for _ in 0..10 {
+            let lazy = lazy.clone();
+            handles.push(compio::runtime::spawn(
+                async move { lazy.force().await.len() },
+            ));
+        }
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 5);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        ONCE_CELL_TEST_TIMEOUT
+    );
+}
@@ -0,0 +1,111 @@
+//! Integration tests for the oneshot channel
+
+use compio_sync::oneshot;
+use std::time::Duration;
+
+/// Timeout for oneshot tests to prevent hanging
+const ONESHOT_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_oneshot_send_from_task() {
+    let result = compio::time::timeout(ONESHOT_TEST_TIMEOUT, async {
+        let (tx, rx) = oneshot::channel();
+
+        compio::runtime::spawn(async move {
+            compio::time::sleep(Duration::from_millis(10)).await;
+            tx.send(vec![1, 2, 3]).unwrap();
+        })
+        .detach();
+
+        assert_eq!(rx.await, Ok(vec![1, 2, 3]));
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        ONESHOT_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_oneshot_sender_dropped_wakes_receiver() {
+    let result = compio::time::timeout(ONESHOT_TEST_TIMEOUT, async {
+        let (tx, rx) = oneshot::channel::<u32>();
+
+        compio::runtime::spawn(async move {
+            compio::time::sleep(Duration::from_millis(10)).await;
+            drop(tx);
+        })
+        .detach();
+
+        assert!(rx.await.is_err());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        ONESHOT_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_oneshot_sender_closed_completes_on_receiver_drop() {
+    let result = compio::time::timeout(ONESHOT_TEST_TIMEOUT, async {
+        let (mut tx, rx) = oneshot::channel::<u32>();
+
+        let producer = compio::runtime::spawn(async move {
+            // Stop as soon as nobody is listening
+            tx.closed().await;
+            tx.send(1)
+        });
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        drop(rx);
+        assert_eq!(producer.await.unwrap(), Err(1));
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        ONESHOT_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_oneshot_failed_send_after_close_ends_receiver() {
+    let result = compio::time::timeout(ONESHOT_TEST_TIMEOUT, async {
+        let (tx, mut rx) = oneshot::channel::<u32>();
+        rx.close();
+
+        compio::runtime::spawn(async move {
+            compio::time::sleep(Duration::from_millis(10)).await;
+            assert_eq!(tx.send(7), Err(7));
+        })
+        .detach();
+
+        // The refused send is the sender's last act, so the wait ends
+        assert!(rx.await.is_err());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        ONESHOT_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_oneshot_receiver_cancelled_by_timeout() {
+    let (tx, mut rx) = oneshot::channel();
+
+    // A timed-out wait leaves the receiver usable
+    let result = compio::time::timeout(Duration::from_millis(10), &mut rx).await;
+    assert!(result.is_err());
+
+    tx.send("late").unwrap();
+    assert_eq!(rx.await, Ok("late"));
+}
@@ -0,0 +1,104 @@
+//! Integration tests for RateLimiter
+
+use compio_sync::RateLimiter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Standard timeout for tests to prevent indefinite hangs
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_rate_limiter_caps_throughput() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        // 200 tokens/s, burst 10: 50 acquires need at least 40 refilled tokens
+        let limiter = RateLimiter::new(200.0, 10);
+        let start = Instant::now();
+
+        for _ in 0..50 {
+            limiter.acquire(1).await;
+        }
+
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(180),
+            "50 tokens at 200/s (burst 10) finished too fast: {:?}",
+            elapsed
+        );
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_rate_limiter_concurrent_waiters_all_complete() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let limiter = Arc::new(RateLimiter::new(500.0, 4));
+        let done = Arc::new(AtomicUsize::new(0));
+        let mut handles = vec![];
+
+        for i in 0..20 {
+            let limiter = limiter.clone();
+            let done = done.clone();
+            handles.push(compio::runtime::spawn(async move {
+                // Mixed request sizes, as with byte-count limiting
+                limiter.acquire(1 + i % 4).await;
+                done.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(done.load(Ordering::SeqCst), 20);
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_rate_limiter_cancelled_waiters_do_not_strand_others() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let limiter = Arc::new(RateLimiter::new(100.0, 2));
+        limiter.acquire(2).await;
+
+        // The first waiter owns the refill timer; the rest are queued
+        let mut cancelled = vec![];
+        for _ in 0..3 {
+            let limiter = limiter.clone();
+            cancelled.push(compio::runtime::spawn(async move {
+                compio::time::timeout(Duration::from_millis(5), limiter.acquire(2))
+                    .await
+                    .is_err()
+            }));
+        }
+        let survivor = {
+            let limiter = limiter.clone();
+            compio::runtime::spawn(async move { limiter.acquire(2).await })
+        };
+
+        for handle in cancelled {
+            assert!(handle.await.unwrap(), "short timeouts should expire");
+        }
+        survivor.await.unwrap();
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_rate_limiter_bytes_per_second() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        // 1 MiB/s in 64 KiB chunks: 256 KiB beyond the burst takes ~250ms
+        let limiter = RateLimiter::new(1024.0 * 1024.0, 64 * 1024);
+        let start = Instant::now();
+
+        for _ in 0..5 {
+            limiter.acquire(64 * 1024).await;
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    })
+    .await
+    .expect("test timed out");
+}
@@ -0,0 +1,157 @@
+//! Integration tests for RwLock
+
+use compio_sync::{RwLock, RwLockWriteGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Standard timeout for tests to prevent indefinite hangs
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_concurrent_readers() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let lock = Arc::new(RwLock::new(42));
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut handles = vec![];
+
+        for _ in 0..10 {
+            let lock = lock.clone();
+            let active = active.clone();
+            let peak = peak.clone();
+            handles.push(compio::runtime::spawn(async move {
+                let guard = lock.read().await;
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                compio::time::sleep(Duration::from_millis(10)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                *guard
+            }));
+        }
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 42);
+        }
+        assert!(peak.load(Ordering::SeqCst) > 1, "readers should overlap");
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_writer_not_starved_by_reader_stream() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let lock = Arc::new(RwLock::new(0u32));
+        let stop = Arc::new(AtomicUsize::new(0));
+
+        // Overlapping readers that would keep the lock read-held forever
+        let mut readers = vec![];
+        for _ in 0..4 {
+            let lock = lock.clone();
+            let stop = stop.clone();
+            readers.push(compio::runtime::spawn(async move {
+                while stop.load(Ordering::SeqCst) == 0 {
+                    let _guard = lock.read().await;
+                    compio::time::sleep(Duration::from_millis(2)).await;
+                }
+            }));
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        compio::time::timeout(Duration::from_secs(2), async {
+            *lock.write().await += 1;
+        })
+        .await
+        .expect("writer starved by readers");
+
+        stop.store(1, Ordering::SeqCst);
+        for reader in readers {
+            reader.await.unwrap();
+        }
+        assert_eq!(*lock.read().await, 1);
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_writers_are_exclusive() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let lock = Arc::new(RwLock::new(Vec::new()));
+        let mut handles = vec![];
+
+        for i in 0..10 {
+            let lock = lock.clone();
+            handles.push(compio::runtime::spawn(async move {
+                let mut guard = lock.clone().write_owned().await;
+                let len = guard.len();
+                compio::time::sleep(Duration::from_millis(1)).await;
+                assert_eq!(guard.len(), len, "another writer ran concurrently");
+                guard.push(i);
+            }));
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(lock.read().await.len(), 10);
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_downgrade_admits_waiting_readers() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let lock = Arc::new(RwLock::new(String::new()));
+        let mut writer = lock.write().await;
+
+        let reader = {
+            let lock = lock.clone();
+            compio::runtime::spawn(async move { lock.read().await.clone() })
+        };
+        compio::time::sleep(Duration::from_millis(5)).await;
+
+        writer.push_str("filled");
+        let downgraded = RwLockWriteGuard::downgrade(writer);
+
+        // The waiting reader runs while we still hold our read guard
+        assert_eq!(reader.await.unwrap(), "filled");
+        assert_eq!(*downgraded, "filled");
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_cancelled_writer_releases_readers() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let lock = Arc::new(RwLock::new(()));
+        let held = lock.read().await;
+
+        // A writer queues (blocking new readers) and then gives up
+        let writer = {
+            let lock = lock.clone();
+            compio::runtime::spawn(async move {
+                compio::time::timeout(Duration::from_millis(20), lock.write())
+                    .await
+                    .is_err()
+            })
+        };
+        compio::time::sleep(Duration::from_millis(5)).await;
+
+        let reader = {
+            let lock = lock.clone();
+            compio::runtime::spawn(async move {
+                drop(lock.read().await);
+            })
+        };
+
+        assert!(writer.await.unwrap());
+        reader.await.unwrap();
+        drop(held);
+    })
+    .await
+    .expect("test timed out");
+}
@@ -11,7 +11,7 @@ const TEST_TIMEOUT: Duration = Duration::from_secs(10);
 async fn test_semaphore_basic_acquire_release() {
     compio::time::timeout(Duration::from_secs(5), async {
         let sem = Semaphore::new(1);
-        let permit = sem.acquire().await;
+        let permit = sem.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 0);
         drop(permit);
         assert_eq!(sem.available_permits(), 1);
@@ -30,7 +30,7 @@ async fn test_semaphore_concurrent_access() {
         for i in 0..20 {
             let sem = sem.clone();
             let handle = compio::runtime::spawn(async move {
-                let _permit = sem.acquire().await;
+                let _permit = sem.acquire().await.unwrap();
                 // Small delay to ensure concurrency
                 compio::time::sleep(Duration::from_millis(10)).await;
                 i
@@ -56,17 +56,17 @@ async fn test_semaphore_try_acquire() {
         let sem = Semaphore::new(1);
 
         let permit1 = sem.try_acquire();
-        assert!(permit1.is_some());
+        assert!(permit1.is_ok());
         assert_eq!(sem.available_permits(), 0);
 
         let permit2 = sem.try_acquire();
-        assert!(permit2.is_none());
+        assert!(permit2.is_err());
 
         drop(permit1);
         assert_eq!(sem.available_permits(), 1);
 
         let permit3 = sem.try_acquire();
-        assert!(permit3.is_some());
+        assert!(permit3.is_ok());
     })
     .await
     .expect("test timed out");
@@ -80,7 +80,7 @@ async fn test_semaphore_multiple_permits() {
         // Acquire 5 permits
         let mut permits = vec![];
         for _ in 0..5 {
-            permits.push(sem.acquire().await);
+            permits.push(sem.acquire().await.unwrap());
         }
 
         assert_eq!(sem.available_permits(), 5);
@@ -108,16 +108,16 @@ async fn test_semaphore_single_permit() {
         assert_eq!(sem.max_permits(), 1);
 
         // Acquire the only permit
-        let permit = sem.acquire().await;
+        let permit = sem.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 0);
 
         // Try to acquire should fail
-        assert!(sem.try_acquire().is_none());
+        assert!(sem.try_acquire().is_err());
 
         // Spawn task that will wait for the permit
         let sem_clone = sem.clone();
         let handle = compio::runtime::spawn(async move {
-            let _permit = sem_clone.acquire().await;
+            let _permit = sem_clone.acquire().await.unwrap();
             "acquired"
         });
 
@@ -148,7 +148,7 @@ async fn test_semaphore_all_waiters_complete() {
         let completed = Arc::new(std::sync::Mutex::new(Vec::new()));
 
         // Hold the semaphore
-        let permit = sem.acquire().await;
+        let permit = sem.acquire().await.unwrap();
 
         // Spawn 5 waiters
         let mut handles = vec![];
@@ -156,7 +156,7 @@ async fn test_semaphore_all_waiters_complete() {
             let sem = sem.clone();
             let completed = completed.clone();
             let handle = compio::runtime::spawn(async move {
-                let _permit = sem.acquire().await;
+                let _permit = sem.acquire().await.unwrap();
                 completed.lock().unwrap().push(i);
             });
             handles.push(handle);
@@ -199,7 +199,7 @@ async fn test_semaphore_stress() {
         for i in 0..1000 {
             let sem = sem.clone();
             let handle = compio::runtime::spawn(async move {
-                let _permit = sem.acquire().await;
+                let _permit = sem.acquire().await.unwrap();
                 // Minimal work
                 i * 2
             });
@@ -227,11 +227,11 @@ async fn test_semaphore_api_methods() {
         assert_eq!(sem.available_permits(), 50);
         assert_eq!(sem.in_use(), 0);
 
-        let _permit1 = sem.acquire().await;
+        let _permit1 = sem.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 49);
         assert_eq!(sem.in_use(), 1);
 
-        let _permit2 = sem.acquire().await;
+        let _permit2 = sem.acquire().await.unwrap();
         assert_eq!(sem.available_permits(), 48);
         assert_eq!(sem.in_use(), 2);
     })
@@ -254,12 +254,12 @@ async fn test_semaphore_lost_wake_race() {
         let sem = Arc::new(Semaphore::new(1));
 
         // Hold the permit
-        let permit = sem.acquire().await;
+        let permit = sem.acquire().await.unwrap();
 
         // Spawn a task that will try to acquire
         let sem_clone = sem.clone();
         let handle = compio::runtime::spawn(async move {
-            sem_clone.acquire().await;
+            sem_clone.acquire().await.unwrap();
         });
 
         // Yield to let the task start and fail try_acquire()
@@ -279,3 +279,216 @@ async fn test_semaphore_lost_wake_race() {
     .await
     .expect("test timed out");
 }
+
+#[compio::test]
+async fn test_semaphore_owned_permit_moves_into_task() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let sem = Arc::new(Semaphore::new(2));
+        let mut handles = vec![];
+
+        // Acquire in the spawning loop so it applies backpressure
+        for i in 0..10 {
+            let permit = sem.clone().acquire_owned().await.unwrap();
+            assert!(sem.available_permits() < 2);
+            handles.push(compio::runtime::spawn(async move {
+                compio::time::sleep(Duration::from_millis(5)).await;
+                drop(permit);
+                i
+            }));
+        }
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.await.unwrap(), i);
+        }
+
+        assert_eq!(sem.available_permits(), 2);
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_semaphore_owned_permit_outlives_handle() {
+    compio::time::timeout(Duration::from_secs(5), async {
+        let sem = Arc::new(Semaphore::new(1));
+        let permit = sem.clone().try_acquire_owned().unwrap();
+
+        // The permit keeps the semaphore alive after the original handle is gone
+        let weak = Arc::downgrade(&sem);
+        drop(sem);
+        assert!(weak.upgrade().is_some());
+
+        let sem = permit.semaphore().clone();
+        assert_eq!(sem.available_permits(), 0);
+        drop(permit);
+        assert_eq!(sem.available_permits(), 1);
+    })
+    .await
+    .expect("test timed out");
+}
+
+#[compio::test]
+async fn test_semaphore_acquire_many_releases_all_on_drop() {
+    compio::time::timeout(Duration::from_secs(5), async {
+        let sem = Arc::new(Semaphore::new(10));
+
+        let permit = sem.acquire_many(7).await.unwrap();
+        assert_eq!(permit.num_permits(), 7);
+        assert_eq!(sem.available_permits(), 3);
+
+        let owned = sem.clone().acquire_many_owned(3).await.unwrap();
+        assert_eq!(owned.num_permits(), 3);
+        assert_eq!(sem.available_permits(), 0);
+
+        // A blocked multi-permit waiter needs both guards to be released
+        let sem_clone = sem.clone();
+        let handle = compio::runtime::spawn(async move {
+            let p = sem_clone.acquire_many(10).await.unwrap();
+            p.num_permits()
+        });
+
+        compio::time::sleep(Duration::from_millis(10)).await;
+        drop(permit);
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(sem.available_permits(), 7);
+
+        drop(owned);
+        assert_eq!(handle.await.unwrap(), 10);
+        assert_eq!(sem.available_permits(), 10);
+    })
+    .await
+    .expect("test timed out");
+}
+
+/// A large request must not be starved forever by a stream of small ones
+#[compio::test]
+async fn test_semaphore_acquire_many_not_starved() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let sem = Arc::new(Semaphore::new(4));
+        let stop = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let mut small = vec![];
+
+        // Keep the semaphore busy with overlapping single-permit holders
+        for _ in 0..8 {
+            let sem = sem.clone();
+            let stop = stop.clone();
+            small.push(compio::runtime::spawn(async move {
+                while !stop.load(std::sync::atomic::Ordering::Acquire) {
+                    let _p = sem.acquire().await.unwrap();
+                    compio::time::sleep(Duration::from_millis(1)).await;
+                }
+            }));
+        }
+
+        compio::time::sleep(Duration::from_millis(5)).await;
+
+        let big = sem.acquire_many(4).await.unwrap();
+        assert_eq!(big.num_permits(), 4);
+        assert_eq!(sem.available_permits(), 0);
+        drop(big);
+
+        stop.store(true, std::sync::atomic::Ordering::Release);
+        for h in small {
+            h.await.unwrap();
+        }
+        assert_eq!(sem.available_permits(), 4);
+    })
+    .await
+    .expect("test timed out");
+}
+
+/// Closing the semaphore lets a pipeline drain: blocked producers stop
+/// instead of waiting forever for permits that will never come back
+#[compio::test]
+async fn test_semaphore_close_drains_pipeline() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let sem = Arc::new(Semaphore::new(3));
+        let mut handles = vec![];
+
+        for _ in 0..10 {
+            let sem = sem.clone();
+            handles.push(compio::runtime::spawn(async move {
+                let mut processed = 0;
+                while let Ok(permit) = sem.acquire().await {
+                    processed += 1;
+                    compio::time::sleep(Duration::from_millis(1)).await;
+                    // Consumer went away: hold the permit until shutdown
+                    std::mem::forget(permit);
+                }
+                processed
+            }));
+        }
+
+        compio::time::sleep(Duration::from_millis(20)).await;
+        sem.close();
+
+        let mut total = 0;
+        for h in handles {
+            total += h.await.unwrap();
+        }
+        assert_eq!(total, 3);
+        assert!(sem.acquire().await.is_err());
+    })
+    .await
+    .expect("test timed out");
+}
+
+/// Capacity can be moved between pipeline stages by splitting a permit
+#[compio::test]
+async fn test_semaphore_split_permit_to_child_task() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let sem = Arc::new(Semaphore::new(8));
+        let mut stage = sem.clone().acquire_many_owned(6).await.unwrap();
+
+        let mut handles = vec![];
+        for _ in 0..3 {
+            let child = stage.split(2).unwrap();
+            handles.push(compio::runtime::spawn(async move {
+                compio::time::sleep(Duration::from_millis(5)).await;
+                child.num_permits()
+            }));
+        }
+        assert_eq!(stage.num_permits(), 0);
+
+        let mut returned = 0;
+        for h in handles {
+            returned += h.await.unwrap();
+        }
+        assert_eq!(returned, 6);
+        assert_eq!(sem.available_permits(), 8);
+    })
+    .await
+    .expect("test timed out");
+}
+
+/// A timed-out waiter must not swallow the wakeup meant for a live waiter
+#[compio::test]
+async fn test_semaphore_acquire_timeout_does_not_strand_waiters() {
+    compio::time::timeout(TEST_TIMEOUT, async {
+        let sem = Arc::new(Semaphore::new(1));
+        let permit = sem.acquire().await.unwrap();
+
+        // A waiter that will give up, queued ahead of one that will not
+        let sem_clone = sem.clone();
+        let impatient = compio::runtime::spawn(async move {
+            sem_clone
+                .acquire_timeout(Duration::from_millis(10))
+                .await
+                .is_err()
+        });
+        compio::time::sleep(Duration::from_millis(1)).await;
+        let sem_clone = sem.clone();
+        let patient =
+            compio::runtime::spawn(async move { sem_clone.acquire().await.unwrap().num_permits() });
+
+        assert!(impatient.await.unwrap());
+        drop(permit);
+
+        compio::time::timeout(Duration::from_millis(500), patient)
+            .await
+            .expect("Live waiter should be woken after a timed-out one left")
+            .unwrap();
+    })
+    .await
+    .expect("test timed out");
+}
@@ -3,6 +3,7 @@
 //! These tests verify behavior under high load and contention.
 
 use compio_sync::Semaphore;
+use std::future::Future;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -22,7 +23,7 @@ async fn test_high_contention_semaphore() {
             let sem = sem.clone();
             let counter = counter.clone();
             handles.push(compio::runtime::spawn(async move {
-                let _p = sem.acquire().await;
+                let _p = sem.acquire().await.unwrap();
                 counter.fetch_add(1, Ordering::Relaxed);
             }));
         }
@@ -56,7 +57,7 @@ async fn test_rapid_acquire_release() {
             let counter = counter.clone();
             handles.push(compio::runtime::spawn(async move {
                 for _ in 0..100 {
-                    let _p = sem.acquire().await;
+                    let _p = sem.acquire().await.unwrap();
                     counter.fetch_add(1, Ordering::Relaxed);
                 }
             }));
@@ -84,14 +85,14 @@ async fn test_many_waiters_all_complete() {
         let sem = Arc::new(Semaphore::new(1));
 
         // Acquire the only permit
-        let permit = sem.acquire().await;
+        let permit = sem.acquire().await.unwrap();
 
         // Spawn many waiters
         let mut handles = vec![];
         for i in 0..100 {
             let sem = sem.clone();
             handles.push(compio::runtime::spawn(async move {
-                let _p = sem.acquire().await;
+                let _p = sem.acquire().await.unwrap();
                 i
             }));
         }
@@ -132,7 +133,7 @@ async fn test_semaphore_under_load_mixed_operations() {
                     let _p = sem.try_acquire();
                 } else {
                     // Wait acquire
-                    let _p = sem.acquire().await;
+                    let _p = sem.acquire().await.unwrap();
                 }
                 i
             }));
@@ -159,13 +160,13 @@ async fn test_future_cancellation_stress() {
         let sem = Arc::new(Semaphore::new(1));
 
         // Hold the permit
-        let permit = sem.acquire().await;
+        let permit = sem.acquire().await.unwrap();
 
         // Start many futures but drop them
         for _ in 0..100 {
             let sem = sem.clone();
             let fut = Box::pin(async move {
-                let _p = sem.acquire().await;
+                let _p = sem.acquire().await.unwrap();
             });
             // Drop immediately (cancel)
             drop(fut);
@@ -173,7 +174,56 @@ async fn test_future_cancellation_stress() {
 
         // Semaphore should still work
         drop(permit);
-        let _p2 = sem.acquire().await;
+        let _p2 = sem.acquire().await.unwrap();
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        STRESS_TEST_TIMEOUT
+    );
+}
+
+/// An acquire woken by `release()` but dropped before it polls must not lose
+/// the wakeup: the next waiter has to get the free permit.
+///
+/// End-to-end check on the platform queue; the semaphore unit tests cover
+/// the same sequence against a queue that does not forward wakes itself.
+#[compio::test]
+async fn test_woken_acquire_cancelled_before_poll() {
+    let result = compio::time::timeout(STRESS_TEST_TIMEOUT, async {
+        let sem = Arc::new(Semaphore::new(1));
+        let permit = sem.acquire().await.unwrap();
+
+        // First in line: polled once by hand so it parks in the queue
+        let mut first = Box::pin(sem.acquire());
+        std::future::poll_fn(|cx| {
+            assert!(first.as_mut().poll(cx).is_pending());
+            std::task::Poll::Ready(())
+        })
+        .await;
+
+        // Second in line, in its own task
+        let second = {
+            let sem = sem.clone();
+            compio::runtime::spawn(async move {
+                let _p = sem.acquire().await.unwrap();
+            })
+        };
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        // release() wakes the first waiter, which loses the race (like the
+        // losing branch of a select) and is dropped without being polled
+        drop(permit);
+        drop(first);
+
+        // The wake is passed on, so the second waiter gets the permit
+        compio::time::timeout(Duration::from_secs(1), second)
+            .await
+            .expect("wakeup lost: permit free but second waiter still asleep")
+            .unwrap();
+        assert_eq!(sem.available_permits(), 1);
     })
     .await;
 
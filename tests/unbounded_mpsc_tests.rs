@@ -0,0 +1,93 @@
+//! Integration tests for the unbounded mpsc channel
+
+use compio_sync::mpsc;
+use std::time::Duration;
+
+/// Timeout for channel tests to prevent hanging
+const UNBOUNDED_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_unbounded_send_from_threads() {
+    let result = compio::time::timeout(UNBOUNDED_TEST_TIMEOUT, async {
+        const THREADS: usize = 4;
+        const PER_THREAD: usize = 10_000;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        // Synchronous producers on other threads, racing on the tail
+        let workers: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let tx = tx.clone();
+                std::thread::spawn(move || {
+                    for i in 0..PER_THREAD {
+                        tx.send((t, i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut next = [0; THREADS];
+        while let Some((t, i)) = rx.recv().await {
+            // Each producer's messages arrive in order
+            assert_eq!(i, next[t]);
+            next[t] += 1;
+        }
+        assert_eq!(next, [PER_THREAD; THREADS]);
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        UNBOUNDED_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_unbounded_receiver_woken_by_send() {
+    let result = compio::time::timeout(UNBOUNDED_TEST_TIMEOUT, async {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let receiver = compio::runtime::spawn(async move {
+            let mut received = Vec::new();
+            while let Some(value) = rx.recv().await {
+                received.push(value);
+            }
+            received
+        });
+
+        for i in 0..5 {
+            compio::time::sleep(Duration::from_millis(2)).await;
+            tx.send(i).unwrap();
+        }
+        drop(tx);
+
+        assert_eq!(receiver.await.unwrap(), vec![0, 1, 2, 3, 4]);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        UNBOUNDED_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_unbounded_close_refuses_sends() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tx.send(1).unwrap();
+    rx.close();
+
+    assert!(tx.is_closed());
+    assert_eq!(tx.send(2), Err(mpsc::SendError(2)));
+    assert_eq!(rx.recv().await, Some(1));
+    // Drained and closed: ends even though the sender is still alive
+    assert_eq!(rx.recv().await, None);
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    drop(tx);
+}
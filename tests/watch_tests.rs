@@ -0,0 +1,158 @@
+//! Integration tests for the watch channel
+
+use compio_sync::{watch, Semaphore};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout for watch tests to prevent hanging
+const WATCH_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[compio::test]
+async fn test_watch_changed_wakes_all_receivers() {
+    let result = compio::time::timeout(WATCH_TEST_TIMEOUT, async {
+        let (tx, rx) = watch::channel(0);
+        let mut handles = vec![];
+
+        for _ in 0..5 {
+            let mut rx = rx.clone();
+            handles.push(compio::runtime::spawn(async move {
+                rx.changed().await.unwrap();
+                *rx.borrow()
+            }));
+        }
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        tx.send(42).unwrap();
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 42);
+        }
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        WATCH_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_watch_change_while_busy_is_not_lost() {
+    let result = compio::time::timeout(WATCH_TEST_TIMEOUT, async {
+        let (tx, mut rx) = watch::channel("initial");
+
+        // Sent before the receiver starts waiting
+        tx.send("reloaded").unwrap();
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow_and_update(), "reloaded");
+
+        // Nothing newer: waits
+        let result = compio::time::timeout(Duration::from_millis(20), rx.changed()).await;
+        assert!(result.is_err());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        WATCH_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_watch_sender_drop_ends_changed() {
+    let result = compio::time::timeout(WATCH_TEST_TIMEOUT, async {
+        let (tx, mut rx) = watch::channel(1);
+
+        let waiter = compio::runtime::spawn(async move {
+            let mut seen = Vec::new();
+            while rx.changed().await.is_ok() {
+                seen.push(*rx.borrow());
+            }
+            seen
+        });
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        tx.send_modify(|v| *v += 1);
+        compio::time::sleep(Duration::from_millis(10)).await;
+        drop(tx);
+
+        assert_eq!(waiter.await.unwrap(), vec![2]);
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        WATCH_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_watch_sender_closed_waits_for_receivers() {
+    let result = compio::time::timeout(WATCH_TEST_TIMEOUT, async {
+        let (tx, rx) = watch::channel(());
+        let rx2 = rx.clone();
+
+        let producer = compio::runtime::spawn(async move {
+            tx.closed().await;
+            tx.is_closed()
+        });
+        compio::time::sleep(Duration::from_millis(10)).await;
+
+        drop(rx);
+        compio::time::sleep(Duration::from_millis(10)).await;
+        drop(rx2);
+        assert!(producer.await.unwrap());
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        WATCH_TEST_TIMEOUT
+    );
+}
+
+#[compio::test]
+async fn test_watch_drives_semaphore_limit() {
+    let result = compio::time::timeout(WATCH_TEST_TIMEOUT, async {
+        let sem = Arc::new(Semaphore::new(4));
+        let (limit_tx, mut limit_rx) = watch::channel(4usize);
+
+        let resizer = {
+            let sem = sem.clone();
+            compio::runtime::spawn(async move {
+                while limit_rx.changed().await.is_ok() {
+                    let limit = *limit_rx.borrow_and_update();
+                    let current = sem.max_permits();
+                    if limit > current {
+                        sem.add_permits(limit - current);
+                    } else {
+                        // Only idle permits can be removed right away
+                        let _ = sem.reduce_permits(current - limit);
+                    }
+                }
+            })
+        };
+
+        limit_tx.send(2).unwrap();
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(sem.max_permits(), 2);
+
+        limit_tx.send(6).unwrap();
+        compio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(sem.max_permits(), 6);
+        assert_eq!(sem.available_permits(), 6);
+
+        drop(limit_tx);
+        resizer.await.unwrap();
+    })
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Test timed out after {:?}",
+        WATCH_TEST_TIMEOUT
+    );
+}